@@ -0,0 +1,117 @@
+//! Backend interface and registry used by the toolchain to pick a codegen.
+//!
+
+use std::collections::HashMap;
+use crate::backends::cpu::CpuBackend;
+use crate::backends::qasm::QasmBackend;
+use crate::ir::ids::BackendKind;
+use crate::ir::modules::HIRModule;
+use crate::utils::errors::BackendError;
+
+
+/// Code generator for a given backend kind.
+///
+pub trait Backend {
+    fn kind(&self) -> BackendKind;
+
+    fn supports(&self, k: BackendKind) -> bool {
+        self.kind() == k
+    }
+
+    /// File extension of the lowered code, ex: `qasm`.
+    fn extension(&self) -> &'static str;
+
+    /// Lower a module into the backend's target code.
+    fn lower(&self, module: &HIRModule) -> Result<String, BackendError>;
+}
+
+
+/// Available backends, looked up by their [`BackendKind`].
+///
+#[derive(Default)]
+pub struct BackendRegistry {
+    backends: HashMap<BackendKind, Box<dyn Backend>>,
+}
+
+impl BackendRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with every backend shipped with the toolchain.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(CpuBackend));
+        registry.register(Box::new(QasmBackend));
+        registry
+    }
+
+    /// Register a backend under its own kind, replacing any previous one.
+    pub fn register(&mut self, backend: Box<dyn Backend>) {
+        self.backends.insert(backend.kind(), backend);
+    }
+
+    pub fn get(&self, k: BackendKind) -> Option<&dyn Backend> {
+        self.backends.get(&k).map(|backend| backend.as_ref())
+    }
+
+    /// Lower a module with the backend registered for `k`.
+    pub fn lower(&self, k: BackendKind, module: &HIRModule) -> Result<String, BackendError> {
+        self.get(k)
+            .ok_or(BackendError::UnsupportedBackend(k))?
+            .lower(module)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::backends::backend::{Backend, BackendRegistry};
+    use crate::backends::qasm::QasmBackend;
+    use crate::ir::hir::Content;
+    use crate::ir::ids::{BackendKind, Path};
+    use crate::ir::modules::HIRModule;
+    use crate::utils::errors::BackendError;
+
+    struct DummyBackend;
+
+    impl Backend for DummyBackend {
+        fn kind(&self) -> BackendKind {
+            BackendKind::CPU
+        }
+
+        fn extension(&self) -> &'static str {
+            "txt"
+        }
+
+        fn lower(&self, _module: &HIRModule) -> Result<String, BackendError> {
+            Ok(String::from("dummy"))
+        }
+    }
+
+    #[test]
+    fn registry_lookup() {
+        let mut registry = BackendRegistry::new();
+        registry.register(Box::new(DummyBackend));
+        registry.register(Box::new(QasmBackend));
+
+        let cpu = registry.get(BackendKind::CPU).unwrap();
+        assert_eq!(cpu.kind(), BackendKind::CPU);
+        assert!(cpu.supports(BackendKind::CPU));
+        assert!(!cpu.supports(BackendKind::QPU));
+        assert_eq!(registry.get(BackendKind::QPU).unwrap().kind(), BackendKind::QPU);
+        assert!(registry.get(BackendKind::GPU).is_none());
+    }
+
+    #[test]
+    fn registry_lower_unregistered() {
+        let module = HIRModule {
+            name: Path::from_dotted("main"),
+            imports: vec![],
+            content: Content::Consts(vec![]),
+        };
+        let registry = BackendRegistry::new();
+        let res = registry.lower(BackendKind::GPU, &module);
+        assert!(matches!(res, Err(BackendError::UnsupportedBackend(BackendKind::GPU))));
+    }
+}
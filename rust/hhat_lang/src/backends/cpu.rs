@@ -0,0 +1,189 @@
+//! Textual three-address IR for CPU content, mostly for debugging.
+//!
+
+use itertools::Itertools;
+use crate::backends::backend::Backend;
+use crate::ir::hir::{Assign, Content, Expr, FnDef, GroupsDef, Literal, Stmt, Symbol};
+use crate::ir::ids::BackendKind;
+use crate::ir::modules::HIRModule;
+use crate::utils::errors::BackendError;
+
+
+/// Textual IR backend for CPU code.
+///
+pub struct CpuBackend;
+
+impl Backend for CpuBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::CPU
+    }
+
+    fn extension(&self) -> &'static str {
+        "ir"
+    }
+
+    fn lower(&self, module: &HIRModule) -> Result<String, BackendError> {
+        lower(module)
+    }
+}
+
+
+/// Lower the functions of a module into a three-address-style IR, one
+/// instruction per line:
+///
+/// ```text
+/// fn area(w: i64 h: i64) i64
+///   decl a: i64
+///   t0 = call mul w h
+///   a = t0
+///   ret a
+/// ```
+///
+/// Call results go into temporaries `t<n>`, numbered from 0 in each
+/// function. Meta-calls, casts, member accesses, struct and enum assignments
+/// and non-CPU symbols or literals are not supported.
+///
+pub fn lower(module: &HIRModule) -> Result<String, BackendError> {
+    let mut code = String::new();
+    if let Content::Groups(groups) = &module.content {
+        for group in groups {
+            if let GroupsDef::FnDef(fn_def) = group {
+                code.push_str(&FnLowering::default().lower(fn_def)?);
+            }
+        }
+    }
+    Ok(code)
+}
+
+/// Instructions of a single function.
+#[derive(Default)]
+struct FnLowering {
+    lines: Vec<String>,
+    temps: usize,
+}
+
+impl FnLowering {
+    fn lower(mut self, fn_def: &FnDef) -> Result<String, BackendError> {
+        let params = fn_def.params.iter()
+            .map(|p| Ok(format!("{}: {}", operand_symbol(&p.name)?, p.ty)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let header = format!("fn {}({}) {}\n", fn_def.name, params.join(" "), fn_def.ty);
+        for stmt in &fn_def.body {
+            self.stmt(stmt)?;
+        }
+        Ok(header + &self.lines.iter().map(|line| format!("  {}\n", line)).join(""))
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> Result<(), BackendError> {
+        match stmt {
+            Stmt::Declare { name, ty, .. } => {
+                self.lines.push(format!("decl {}: {}", operand_symbol(name)?, ty));
+            }
+            Stmt::DeclareAssign { name, ty, value, .. } => {
+                let name = operand_symbol(name)?;
+                self.lines.push(format!("decl {}: {}", name, ty));
+                let value = self.expr(value)?;
+                self.lines.push(format!("{} = {}", name, value));
+            }
+            Stmt::Assign(Assign::Single { name, value, .. }) => {
+                let name = operand_symbol(name)?;
+                let value = self.expr(value)?;
+                self.lines.push(format!("{} = {}", name, value));
+            }
+            Stmt::Expr(expr) => {
+                self.expr(expr)?;
+            }
+            Stmt::Return(expr) => {
+                let value = self.expr(expr)?;
+                self.lines.push(format!("ret {}", value));
+            }
+            Stmt::Assign(_) => return Err(unsupported(stmt)),
+        }
+        Ok(())
+    }
+
+    /// Lower an expression into an operand, a name, literal or temporary.
+    fn expr(&mut self, expr: &Expr) -> Result<String, BackendError> {
+        match expr {
+            Expr::Id(sym) => operand_symbol(sym),
+            Expr::Literal(lit) => operand_literal(lit),
+            Expr::Call { callee, args, .. } => {
+                let callee = operand_symbol(callee)?;
+                let args = args.iter()
+                    .map(|arg| self.expr(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let temp = format!("t{}", self.temps);
+                self.temps += 1;
+                self.lines.push(
+                    format!("{} = call {}", temp, std::iter::once(callee).chain(args).join(" "))
+                );
+                Ok(temp)
+            }
+            Expr::MetaCall(_) | Expr::Cast { .. } | Expr::DataMemberAccess(_) => {
+                Err(unsupported(expr))
+            }
+        }
+    }
+}
+
+fn operand_symbol(sym: &Symbol) -> Result<String, BackendError> {
+    match sym.backend_kind {
+        BackendKind::CPU => Ok(sym.to_string()),
+        _ => Err(unsupported(sym)),
+    }
+}
+
+fn operand_literal(lit: &Literal) -> Result<String, BackendError> {
+    match lit {
+        Literal::Bool(_, BackendKind::CPU, _)
+        | Literal::Int(_, BackendKind::CPU, _)
+        | Literal::Float(_, BackendKind::CPU, _)
+        | Literal::Str(_, BackendKind::CPU, _) => Ok(lit.to_string()),
+        _ => Err(unsupported(lit)),
+    }
+}
+
+fn unsupported(construct: impl ToString) -> BackendError {
+    BackendError::UnsupportedConstruct { construct: construct.to_string() }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::backends::cpu::lower;
+    use crate::ir::hir::{Content, GroupsDef};
+    use crate::ir::ids::Path;
+    use crate::ir::modules::HIRModule;
+    use crate::parse::parser::fn_program;
+    use crate::utils::errors::BackendError;
+
+    fn module(code: &str) -> HIRModule {
+        let fn_def = fn_program::fn_def(code).unwrap();
+        HIRModule {
+            name: Path::from_dotted("main"),
+            imports: vec![],
+            content: Content::Groups(vec![GroupsDef::FnDef(fn_def)]),
+        }
+    }
+
+    #[test]
+    fn lower_function() {
+        let code = "fn area(w: i64 h: i64) i64 { a: i64 = mul(w add(h 1)) print(a) return a }";
+        let ir = lower(&module(code)).unwrap();
+        assert_eq!(ir, "\
+fn area(w: i64 h: i64) i64
+  decl a: i64
+  t0 = call add h 1
+  t1 = call mul w t0
+  a = t1
+  t2 = call print a
+  ret a
+");
+    }
+
+    #[test]
+    fn lower_qpu_call() {
+        let res = lower(&module("fn bell() null { @h(@q0) }"));
+        assert!(matches!(res, Err(BackendError::UnsupportedConstruct { construct }) if construct == "@h"));
+    }
+}
@@ -0,0 +1,3 @@
+pub(crate) mod backend;
+pub(crate) mod cpu;
+pub(crate) mod qasm;
@@ -0,0 +1,205 @@
+//! OpenQASM 3 code generation for QPU content.
+//!
+
+use itertools::Itertools;
+use crate::backends::backend::Backend;
+use crate::ir::hir::{Block, Content, Expr, GroupsDef, MetaCall, Stmt, Symbol};
+use crate::ir::ids::BackendKind;
+use crate::ir::modules::HIRModule;
+use crate::ir::visit::{walk_meta_call, ExprVisitor};
+use crate::runtime::interpreter::RuntimeError;
+use crate::runtime::staged::{StagedPlan, DEFAULT_SHOTS};
+use crate::utils::errors::BackendError;
+
+
+/// Gates available to QPU calls, named as in OpenQASM's `stdgates.inc`.
+pub const GATES: &[&str] = &["h", "x", "cx"];
+
+
+/// OpenQASM 3 backend for QPU code.
+///
+pub struct QasmBackend;
+
+impl Backend for QasmBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::QPU
+    }
+
+    fn extension(&self) -> &'static str {
+        "qasm"
+    }
+
+    fn lower(&self, module: &HIRModule) -> Result<String, BackendError> {
+        emit_qasm(module)
+    }
+}
+
+
+/// Lower the QPU calls of a module into an OpenQASM 3 program.
+///
+/// Only calls whose callee is QPU-tagged (`@h(@q0)`) are emitted, including
+/// the ones in the body of a QPU meta-call (`@run(@q0)<shots=10>{ @h(@q0) }`),
+/// whose `shots` modifier is kept as for [`StagedPlan::stage_stmt`]. CPU code
+/// without QPU calls is skipped; QPU calls anywhere else fail with
+/// [`BackendError::UnsupportedConstruct`]. See [`emit_plan`] for the program
+/// layout.
+///
+pub fn emit_qasm(module: &HIRModule) -> Result<String, BackendError> {
+    let mut plan = StagedPlan::new();
+    if let Content::Groups(groups) = &module.content {
+        for group in groups {
+            if let GroupsDef::FnDef(fn_def) = group {
+                stage_calls(&fn_def.body, &mut plan)?;
+            }
+        }
+    }
+    emit_plan(&plan)
+}
+
+fn stage_calls(block: &Block, plan: &mut StagedPlan) -> Result<(), BackendError> {
+    let lowering = |e: RuntimeError| BackendError::LoweringFailed(e.to_string());
+    for stmt in block {
+        match stmt {
+            Stmt::Expr(Expr::Call { callee, args, modifiers }) if callee.backend_kind == BackendKind::QPU => {
+                plan.stage_call(callee, args, modifiers).map_err(lowering)?;
+            }
+            Stmt::Expr(Expr::MetaCall(MetaCall::Bdn { name, body, modifiers, .. }))
+                if name.backend_kind == BackendKind::QPU =>
+            {
+                plan.record_shots(modifiers).map_err(lowering)?;
+                stage_calls(body, plan)?;
+            }
+            _ => {
+                let mut finder = QpuFinder::default();
+                finder.visit_stmt(stmt);
+                if finder.found {
+                    return Err(BackendError::UnsupportedConstruct { construct: stmt.to_string() });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Looks for QPU calls and meta-calls anywhere in a statement.
+#[derive(Default)]
+struct QpuFinder {
+    found: bool,
+}
+
+impl ExprVisitor for QpuFinder {
+    fn visit_call(&mut self, callee: &Symbol, args: &[Expr]) {
+        self.found |= callee.backend_kind == BackendKind::QPU;
+        for arg in args {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_meta_call(&mut self, call: &MetaCall) {
+        let (MetaCall::Optn { name, .. } | MetaCall::Bdn { name, .. } | MetaCall::OptBdn { name, .. }) = call;
+        self.found |= name.backend_kind == BackendKind::QPU;
+        walk_meta_call(self, call);
+    }
+}
+
+/// Emit a staged plan as an OpenQASM 3 program.
+///
+/// The plan's shots (1 by default) go in a `pragma shots` line. Qubits `<n>`
+/// map to `q[<n>]` of a single register sized to the highest index used,
+/// which is measured into a bit register of the same size at the end.
+///
+pub fn emit_plan(plan: &StagedPlan) -> Result<String, BackendError> {
+    let ops = plan.finalize();
+    let num_qubits = ops.iter()
+        .flat_map(|op| op.qubits.iter())
+        .max()
+        .map_or(0, |n| n + 1);
+
+    let mut code = String::from("OPENQASM 3;\ninclude \"stdgates.inc\";\n");
+    code.push_str(&format!("pragma shots {}\n", plan.shots.unwrap_or(DEFAULT_SHOTS)));
+    if num_qubits > 0 {
+        code.push_str(&format!("qubit[{}] q;\nbit[{}] c;\n", num_qubits, num_qubits));
+    }
+    for op in &ops {
+        let qubits = op.qubits.iter().map(|n| format!("q[{}]", n)).join(", ");
+        code.push_str(&format!("{} {};\n", gate_name(&op.gate)?, qubits));
+    }
+    if num_qubits > 0 {
+        code.push_str("c = measure q;\n");
+    }
+    Ok(code)
+}
+
+fn gate_name(gate: &str) -> Result<&'static str, BackendError> {
+    GATES.iter()
+        .find(|g| **g == gate)
+        .copied()
+        .ok_or_else(|| BackendError::UnsupportedConstruct {
+            construct: format!("{}{}", BackendKind::QPU.sugar_str(), gate),
+        })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::backends::qasm::emit_qasm;
+    use crate::ir::hir::{Content, GroupsDef};
+    use crate::ir::ids::Path;
+    use crate::ir::modules::HIRModule;
+    use crate::parse::parser::fn_program;
+    use crate::utils::errors::BackendError;
+
+    fn module(code: &str) -> HIRModule {
+        let fn_def = fn_program::fn_def(code).unwrap();
+        HIRModule {
+            name: Path::from_dotted("main"),
+            imports: vec![],
+            content: Content::Groups(vec![GroupsDef::FnDef(fn_def)]),
+        }
+    }
+
+    #[test]
+    fn emit_two_gates() {
+        let code = "fn main() null { @h(@q0) print(x) @cx(@q0 @q1) }";
+        let qasm = emit_qasm(&module(code)).unwrap();
+        assert_eq!(
+            qasm,
+            "OPENQASM 3;\ninclude \"stdgates.inc\";\npragma shots 1\nqubit[2] q;\nbit[2] c;\nh q[0];\ncx q[0], q[1];\nc = measure q;\n"
+        );
+    }
+
+    #[test]
+    fn emit_shots() {
+        let qasm = emit_qasm(&module("fn main() null { @h(@q0)<shots=2048> }")).unwrap();
+        assert!(qasm.contains("pragma shots 2048\n"));
+
+        let res = emit_qasm(&module("fn main() null { @h(@q0)<shots=1> @h(@q0)<shots=2> }"));
+        assert!(matches!(res, Err(BackendError::LoweringFailed(_))));
+    }
+
+    #[test]
+    fn emit_meta_call_body() {
+        let code = "fn main() null { @run(@q0)<shots=10>{ @h(@q0) @cx(@q0 @q1) } }";
+        let qasm = emit_qasm(&module(code)).unwrap();
+        assert_eq!(
+            qasm,
+            "OPENQASM 3;\ninclude \"stdgates.inc\";\npragma shots 10\nqubit[2] q;\nbit[2] c;\nh q[0];\ncx q[0], q[1];\nc = measure q;\n"
+        );
+
+        let res = emit_qasm(&module("fn main() null { loop(3){ @h(@q0) } }"));
+        assert!(matches!(res, Err(BackendError::UnsupportedConstruct { .. })));
+        assert!(emit_qasm(&module("fn main() null { loop(3){ print(1) } }")).is_ok());
+    }
+
+    #[test]
+    fn emit_unknown_gate() {
+        let res = emit_qasm(&module("fn main() null { @foo(@q0) }"));
+        assert!(matches!(res, Err(BackendError::UnsupportedConstruct { construct }) if construct == "@foo"));
+    }
+
+    #[test]
+    fn emit_non_qubit_argument() {
+        let res = emit_qasm(&module("fn main() null { @h(x) }"));
+        assert!(matches!(res, Err(BackendError::LoweringFailed(_))));
+    }
+}
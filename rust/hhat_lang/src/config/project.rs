@@ -0,0 +1,85 @@
+//! Project configuration, read from the `hat.toml` file at the project root.
+//!
+
+use std::fs::read_to_string;
+use std::path::Path;
+use serde::Deserialize;
+use crate::ir::ids::BackendKind;
+use crate::utils::errors::ProjectError;
+
+
+/// Name of the configuration file, at the project root.
+pub const CONFIG_FILE: &str = "hat.toml";
+
+
+/// Project settings. Missing fields take their default values.
+///
+/// ```toml
+/// default_backend = "QPU"
+/// shots = 1000
+/// sources = ["src"]
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub default_backend: BackendKind,
+    pub shots: Option<u32>,
+    pub sources: Option<Vec<String>>,
+}
+
+impl Default for ProjectConfig {
+    fn default() -> Self {
+        Self { default_backend: BackendKind::CPU, shots: None, sources: None }
+    }
+}
+
+impl ProjectConfig {
+    /// Load `hat.toml` from `root`, or the default config if there is none.
+    pub fn load(root: &Path) -> Result<ProjectConfig, ProjectError> {
+        let path = root.join(CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(ProjectConfig::default());
+        }
+        let raw = read_to_string(&path)
+            .map_err(|e| ProjectError::ConfigInvalid(e.to_string()))?;
+        ProjectConfig::parse(&raw)
+    }
+
+    pub fn parse(raw: &str) -> Result<ProjectConfig, ProjectError> {
+        toml::from_str(raw).map_err(|e| ProjectError::ConfigInvalid(e.message().to_string()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::config::project::{ProjectConfig, CONFIG_FILE};
+    use crate::ir::ids::BackendKind;
+    use crate::utils::errors::ProjectError;
+
+    #[test]
+    fn parse_config() {
+        let config = ProjectConfig::parse(
+            "default_backend = \"QPU\"\nshots = 1000\nsources = [\"src\", \"lib\"]\n"
+        ).unwrap();
+        assert_eq!(config.default_backend, BackendKind::QPU);
+        assert_eq!(config.shots, Some(1000));
+        assert_eq!(config.sources, Some(vec![String::from("src"), String::from("lib")]));
+    }
+
+    #[test]
+    fn load_config() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(ProjectConfig::load(dir.path()).unwrap(), ProjectConfig::default());
+
+        fs::write(dir.path().join(CONFIG_FILE), "shots = 10").unwrap();
+        let config = ProjectConfig::load(dir.path()).unwrap();
+        assert_eq!(config.default_backend, BackendKind::CPU);
+        assert_eq!(config.shots, Some(10));
+
+        fs::write(dir.path().join(CONFIG_FILE), "default_backend = \"XPU\"").unwrap();
+        assert!(matches!(ProjectConfig::load(dir.path()), Err(ProjectError::ConfigInvalid(_))));
+    }
+}
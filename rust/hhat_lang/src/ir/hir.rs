@@ -4,15 +4,25 @@
 
 use std::borrow::Borrow;
 use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 use crate::ir::ids::{BackendKind, ExprId, Path};
 use itertools::Itertools;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
 
 /// Identifier for HIR.
 ///
+/// The span holds the byte offsets (start, end) of the symbol in
+/// the source code, when it is known. It is not part of the symbol's
+/// identity: equality and hashing only look at the value and backend kind.
+///
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Symbol {
     pub value: String,
     pub backend_kind: BackendKind,
+    pub span: Option<(usize, usize)>,
 }
 
 impl Display for Symbol {
@@ -21,9 +31,36 @@ impl Display for Symbol {
     }
 }
 
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.backend_kind == other.backend_kind
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+        self.backend_kind.hash(state);
+    }
+}
+
 impl Symbol {
     pub fn new(value: String, backend_kind: BackendKind) -> Self {
-        Self { value, backend_kind }
+        Self { value, backend_kind, span: None }
+    }
+
+    pub fn with_span(value: String, backend_kind: BackendKind, span: (usize, usize)) -> Self {
+        Self { value, backend_kind, span: Some(span) }
+    }
+
+    /// Index of a qubit reference, ex: `@q0` -> `0`.
+    pub fn qubit_index(&self) -> Option<usize> {
+        if self.backend_kind != BackendKind::QPU {
+            return None;
+        }
+        self.value.strip_prefix('q').and_then(|n| n.parse().ok())
     }
 }
 
@@ -32,6 +69,8 @@ impl Symbol {
 ///
 /// Can be used for calling enums, for instance.
 ///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct CompositeSymbol {
     pub value: Vec<Symbol>,
 }
@@ -56,6 +95,8 @@ impl CompositeSymbol {
 
 /// Symbols with path for importing purposes for HIR.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct ImportPathSymbol {
     pub name: Symbol,
     pub path: Path,
@@ -64,7 +105,7 @@ pub struct ImportPathSymbol {
 impl Display for ImportPathSymbol {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let glue_path_name: String = String::from(
-            if self.path.len() > 0 { "." } else { "" }
+            if !self.path.is_empty() { "." } else { "" }
         );
         write!(f, "{}{}{}", self.path, glue_path_name, self.name)
     }
@@ -84,6 +125,8 @@ impl ImportPathSymbol {
 /// Imports cannot have alias for now, so constants and types
 /// must have unique names.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Imports {
     Consts(Vec<ImportPathSymbol>),
     Types(Vec<ImportPathSymbol>),
@@ -92,11 +135,57 @@ pub enum Imports {
     MetaFns(Vec<ImportPathSymbol>),
 }
 
+impl Imports {
+    /// Add `new` to `existing`, appending its paths to the entry of the
+    /// same kind if there is one, so there is at most one entry per kind.
+    pub fn merge(existing: &mut Vec<Imports>, new: Imports) {
+        let entry = existing.iter_mut().find(|imports| {
+            std::mem::discriminant(*imports) == std::mem::discriminant(&new)
+        });
+        match entry {
+            Some(entry) => entry.paths_mut().extend(new.into_paths()),
+            None => existing.push(new),
+        }
+    }
+
+    pub fn paths(&self) -> &[ImportPathSymbol] {
+        match self {
+            Imports::Consts(paths)
+            | Imports::Types(paths)
+            | Imports::Fns(paths)
+            | Imports::Modifiers(paths)
+            | Imports::MetaFns(paths) => paths,
+        }
+    }
+
+    fn paths_mut(&mut self) -> &mut Vec<ImportPathSymbol> {
+        match self {
+            Imports::Consts(paths)
+            | Imports::Types(paths)
+            | Imports::Fns(paths)
+            | Imports::Modifiers(paths)
+            | Imports::MetaFns(paths) => paths,
+        }
+    }
+
+    fn into_paths(self) -> Vec<ImportPathSymbol> {
+        match self {
+            Imports::Consts(paths)
+            | Imports::Types(paths)
+            | Imports::Fns(paths)
+            | Imports::Modifiers(paths)
+            | Imports::MetaFns(paths) => paths,
+        }
+    }
+}
+
 
 /// Constant definition for HIR.
 ///
 /// It must exist in a constants-only file.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct ConstDef {
     pub name: Symbol,
     pub ty: TypeName,
@@ -108,19 +197,85 @@ pub struct ConstDef {
 ///
 /// Any given platform can define its own literals:
 /// ```rust
-/// Int(7, BackendKind::CPU)  // 7 on CPU
-/// Int(3, BackendKind::QPU)  // @3, syntax sugar for 3 on QPU
+/// Int(7, BackendKind::CPU, None)  // 7 on CPU
+/// Int(3, BackendKind::QPU, None)  // @3, syntax sugar for 3 on QPU
 /// ```
+///
+/// Like [`Symbol`], a literal holds its byte offsets (start, end) in the
+/// source code when it is known, and they are not part of its equality.
+///
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Literal {
-    Bool(bool, BackendKind),
-    Int(i64, BackendKind),
-    Float(f64, BackendKind),
-    Str(String, BackendKind),
+    Bool(bool, BackendKind, Option<(usize, usize)>),
+    Int(i64, BackendKind, Option<(usize, usize)>),
+    Float(f64, BackendKind, Option<(usize, usize)>),
+    Str(String, BackendKind, Option<(usize, usize)>),
+}
+
+impl PartialEq for Literal {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Literal::Bool(a, ka, _), Literal::Bool(b, kb, _)) => a == b && ka == kb,
+            (Literal::Int(a, ka, _), Literal::Int(b, kb, _)) => a == b && ka == kb,
+            (Literal::Float(a, ka, _), Literal::Float(b, kb, _)) => a == b && ka == kb,
+            (Literal::Str(a, ka, _), Literal::Str(b, kb, _)) => a == b && ka == kb,
+            _ => false,
+        }
+    }
+}
+
+impl Literal {
+    pub fn span(&self) -> Option<(usize, usize)> {
+        match self {
+            Literal::Bool(.., span)
+            | Literal::Int(.., span)
+            | Literal::Float(.., span)
+            | Literal::Str(.., span) => *span,
+        }
+    }
+
+    pub fn with_span(mut self, span: (usize, usize)) -> Self {
+        match &mut self {
+            Literal::Bool(.., s) | Literal::Int(.., s) | Literal::Float(.., s) | Literal::Str(.., s) => {
+                *s = Some(span)
+            }
+        }
+        self
+    }
+}
+
+/// Prints the literal with its backend sugar, ex: `@3`. Floats keep a
+/// fractional part (`1.0`), and strings are quoted with `"`, `\` and line
+/// breaks escaped.
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal::Bool(v, kind, _) => write!(f, "{}{}", kind.sugar_str(), v),
+            Literal::Int(v, kind, _) => write!(f, "{}{}", kind.sugar_str(), v),
+            Literal::Float(v, kind, _) => write!(f, "{}{:?}", kind.sugar_str(), v),
+            Literal::Str(v, kind, _) => {
+                write!(f, "{}\"", kind.sugar_str())?;
+                for c in v.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        '\r' => write!(f, "\\r")?,
+                        _ => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+        }
+    }
 }
 
 
 /// Expression for HIR.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Expr {
     Id(Symbol),
     Literal(Literal),
@@ -142,12 +297,32 @@ pub enum Expr {
     DataMemberAccess(CompositeSymbol),
 }
 
+/// Prints the expression back in source syntax.
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Id(sym) => write!(f, "{}", sym),
+            Expr::Literal(lit) => write!(f, "{}", lit),
+            Expr::Call { callee, args, modifiers } => {
+                write!(f, "{}({}){}", callee, args.iter().format(" "), modifiers.iter().format(""))
+            }
+            Expr::MetaCall(call) => write!(f, "{}", call),
+            Expr::Cast { value, to_ty, modifiers } => {
+                write!(f, "{} * {}{}", value, to_ty, modifiers.iter().format(""))
+            }
+            Expr::DataMemberAccess(composite) => write!(f, "{}", composite),
+        }
+    }
+}
+
 
 /// Meta-function calls:
 /// - [`MetaCall::Optn`] (option/cases functions)
 /// - [`MetaCall::Bdn`] (body/blocks functions)
 /// - [`MetaCall::OptBdn`] (option-body/case-block functions)
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum MetaCall {
     /// Option functions (cases).
     ///
@@ -177,27 +352,74 @@ pub enum MetaCall {
     },
 }
 
+impl Display for MetaCall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetaCall::Optn { name, options, modifiers } => {
+                write!(f, "{}({}){}", name, options.iter().format(" "), modifiers.iter().format(""))
+            }
+            MetaCall::Bdn { name, args, body, modifiers } => write!(
+                f,
+                "{}({}){}{}",
+                name,
+                args.iter().format(" "),
+                modifiers.iter().format(""),
+                body
+            ),
+            MetaCall::OptBdn { name, args, body, modifiers } => write!(
+                f,
+                "{}({}){}{{{}}}",
+                name,
+                args.iter().format(" "),
+                modifiers.iter().format(""),
+                body.iter().format(" ")
+            ),
+        }
+    }
+}
+
 
 /// Option + body for HIR.
 ///
 /// Syntax: `opt:{body}`
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct OptionBody {
     pub opt: Expr,
     pub body: Block,
 }
 
+impl Display for OptionBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.opt, self.body)
+    }
+}
+
 
 /// Modifiers calls for HIR.
 ///
 /// It can be no-arg modifiers (`<&>`, `<mut>`) or
 /// single-arg modifiers (`<shots=1000>`, `<device=qiskit.aer-sim>`).
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Modifier {
     pub name: Symbol,
     pub value: Option<Expr>,
 }
 
+impl Display for Modifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "<{}={}>", self.name, value),
+            None => write!(f, "<{}>", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct StructDef {
     pub name: Symbol,
     pub members: Vec<StructMember>,
@@ -205,6 +427,8 @@ pub struct StructDef {
 }
 
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct StructMember {
     pub name: Symbol,
     pub ty: TypeName
@@ -216,12 +440,34 @@ pub struct StructMember {
 /// It contains the name (as a [`Symbol`]) and its
 /// backend kind (as a [`BackendKind`]).
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct TypeName {
     pub name: Symbol,
-    pub modifiers: Modifier,
+    pub modifiers: Vec<Modifier>,
 }
 
+/// Prints the modifiers before the name, as they are parsed, ex: `<mut> i32`
+impl Display for TypeName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", prefix_modifiers(&self.modifiers), self.name)
+    }
+}
 
+impl TypeName {
+    /// Type left to be inferred from the assigned value.
+    pub fn placeholder() -> Self {
+        Self { name: Symbol::new(String::new(), BackendKind::CPU), modifiers: vec![] }
+    }
+
+    pub fn is_placeholder(&self) -> bool {
+        self.name.value.is_empty()
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum EnumMember {
     /// Enum member as a single value:
     ///
@@ -235,6 +481,9 @@ pub enum EnumMember {
     StructMember(StructDef),
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[allow(clippy::upper_case_acronyms)]
 pub enum PrimitiveDef {
     BOOL,
     U32,
@@ -246,11 +495,30 @@ pub enum PrimitiveDef {
     STR
 }
 
+impl PrimitiveDef {
+    /// Primitive type from its name in the source, ex: `"i32"`.
+    pub fn from_name(name: &str) -> Option<PrimitiveDef> {
+        match name {
+            "bool" => Some(PrimitiveDef::BOOL),
+            "u32" => Some(PrimitiveDef::U32),
+            "u64" => Some(PrimitiveDef::U64),
+            "i32" => Some(PrimitiveDef::I32),
+            "i64" => Some(PrimitiveDef::I64),
+            "f32" => Some(PrimitiveDef::F32),
+            "f64" => Some(PrimitiveDef::F64),
+            "str" => Some(PrimitiveDef::STR),
+            _ => None,
+        }
+    }
+}
+
 
 /// Type definition for HIR.
 ///
 /// It must exist in a types-only file.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum TypeDef {
     PrimitiveDef(PrimitiveDef),
     StructDef(StructDef),
@@ -274,6 +542,9 @@ pub enum TypeDef {
 /// modifiers and meta-functions definitions. They can
 /// co-exist in the same file.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[allow(clippy::large_enum_variant, clippy::enum_variant_names)]
 pub enum GroupsDef {
     FnDef(FnDef),
     ModifierDef(ModifierDef),
@@ -283,6 +554,8 @@ pub enum GroupsDef {
 
 /// Function definition for HIR.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct FnDef {
     pub name: Symbol,
     pub params: Vec<Param>,
@@ -292,6 +565,8 @@ pub struct FnDef {
 }
 
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Param {
     pub name: Symbol,
     pub ty: TypeName,
@@ -301,43 +576,110 @@ pub struct Param {
 
 /// Block of code for HIR.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Block(Vec<Stmt>);
 
+impl Block {
+    pub fn new(stmts: Vec<Stmt>) -> Self {
+        Self(stmts)
+    }
+
+    pub fn as_slice(&self) -> &[Stmt] {
+        &self.0
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Stmt> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a Block {
+    type Item = &'a Stmt;
+    type IntoIter = std::slice::Iter<'a, Stmt>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl Display for Block {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            write!(f, "{{}}")
+        } else {
+            write!(f, "{{ {} }}", self.0.iter().format(" "))
+        }
+    }
+}
+
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Assign {
     Single {
         name: Symbol,
         value: Expr,
         modifiers: Vec<Modifier>,
     },
+    /// Struct initialization, ex: `p = point{ x:1 y:2 }`
     Struct {
+        name: Symbol,
         ty: Option<Symbol>,
         members: Vec<StructMembersInit>,
     },
     Enum {
+        name: Symbol,
         ty: Symbol,
         members: EnumMembersInit,
     },
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct DeclareAssign {
     name: Symbol,
 
 }
 
 
+/// Struct member being initialized, ex: `x:1`
+///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct StructMembersInit {
-    name: Symbol,
-    value: Expr,
+    pub name: Symbol,
+    pub value: Expr,
+}
+
+impl StructMembersInit {
+    pub fn new(name: Symbol, value: Expr) -> Self {
+        Self { name, value }
+    }
 }
 
 
+/// Enum member being assigned: either a plain kind, ex: `red`, or a
+/// struct-like member with its initialized members.
+///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum EnumMembersInit {
-    EnumMember(),
-    StructMember(),
+    Kind(Symbol),
+    Struct(Vec<StructMembersInit>),
 }
 
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum AssignDef {
     SingleMemberAssign,
     FullAssign,
@@ -346,6 +688,8 @@ pub enum AssignDef {
 
 /// Statements for HIR.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Stmt {
     Declare {
         name: Symbol,
@@ -363,9 +707,58 @@ pub enum Stmt {
     Return(Expr),
 }
 
+/// Prints the statement back in source syntax.
+impl Display for Stmt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stmt::Declare { name, ty, modifiers } => {
+                write!(f, "{}{}: {}", prefix_modifiers(modifiers), name, ty)
+            }
+            Stmt::Assign(Assign::Single { name, value, modifiers }) => {
+                write!(f, "{}{} = {}", prefix_modifiers(modifiers), name, value)
+            }
+            Stmt::Assign(Assign::Struct { name, ty, members }) => write!(
+                f,
+                "{} = {}{{{}}}",
+                name,
+                ty.as_ref().map(Symbol::to_string).unwrap_or_default(),
+                format_members(members)
+            ),
+            Stmt::Assign(Assign::Enum { name, ty, members: EnumMembersInit::Kind(kind) }) => {
+                write!(f, "{} = {}.{}", name, ty, kind)
+            }
+            Stmt::Assign(Assign::Enum { name, ty, members: EnumMembersInit::Struct(members) }) => {
+                write!(f, "{} = {}{{{}}}", name, ty, format_members(members))
+            }
+            Stmt::DeclareAssign { name, ty, modifiers, value } => write!(
+                f,
+                "{}{}: {} = {}",
+                prefix_modifiers(modifiers),
+                name,
+                ty,
+                value
+            ),
+            Stmt::Expr(expr) => write!(f, "{}", expr),
+            Stmt::Return(expr) => write!(f, "return {}", expr),
+        }
+    }
+}
+
+/// Modifiers placed before a name, ex: `<mut> <&> `
+pub(crate) fn prefix_modifiers(modifiers: &[Modifier]) -> String {
+    modifiers.iter().map(|m| format!("{} ", m)).collect()
+}
+
+/// Struct members initialization, ex: `x:1 y:2`
+fn format_members(members: &[StructMembersInit]) -> impl Display + '_ {
+    members.iter().format_with(" ", |m, g| g(&format_args!("{}:{}", m.name, m.value)))
+}
+
 
 /// Modifier definition for HIR.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct ModifierDef {
     pub name: Symbol,
     pub params: [Option<Param>; 2],
@@ -377,6 +770,8 @@ pub struct ModifierDef {
 
 /// Meta-function definition for HIR.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct MetaFnDef {
     pub name: Symbol,
     pub params: Vec<Param>,
@@ -391,8 +786,151 @@ pub struct MetaFnDef {
 /// or groups ([`GroupsDef`]). Each one of these contents must not
 /// be mixed with the others within the same file.
 ///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Content {
     Consts(Vec<ConstDef>),
     Types(Vec<TypeDef>),
     Groups(Vec<GroupsDef>),
 }
+
+
+/// Category of a file's content, matching the variants of [`Content`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum ContentKind {
+    Consts,
+    Types,
+    Groups,
+}
+
+
+/// Serialize a module's content to JSON, for external tooling.
+#[cfg(feature = "serde")]
+pub fn to_json(content: &Content) -> Result<String, serde_json::Error> {
+    serde_json::to_string(content)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::ir::hir::{Block, CompositeSymbol, Imports, Literal, Modifier, Symbol, TypeName};
+    use crate::ir::ids::BackendKind;
+    use crate::parse::parser::fn_program;
+
+    #[test]
+    fn merge_imports() {
+        let mut imports = vec![];
+        for code in ["use fns a.f", "use types a.T", "use fns b.g"] {
+            Imports::merge(&mut imports, fn_program::import(code).unwrap());
+        }
+        assert_eq!(imports.len(), 2);
+        let Imports::Fns(fns) = &imports[0] else {
+            panic!("expected fns import");
+        };
+        let names: Vec<String> = fns.iter().map(|s| s.to_string()).collect();
+        assert_eq!(names, ["a.f", "b.g"]);
+    }
+
+    #[test]
+    fn type_name_with_modifiers() {
+        let modifier = |name: &str| Modifier {
+            name: Symbol::new(String::from(name), BackendKind::CPU),
+            value: None,
+        };
+        let ty = TypeName {
+            name: Symbol::new(String::from("i32"), BackendKind::CPU),
+            modifiers: vec![modifier("mut"), modifier("&")],
+        };
+        assert_eq!(ty.modifiers.len(), 2);
+        assert_eq!(ty.to_string(), "<mut> <&> i32");
+    }
+
+    #[test]
+    fn parsed_exprs_compare_equal() {
+        let a = fn_program::expr("add(x @q0) * f64<&>").unwrap();
+        let b = fn_program::expr("add(x @q0) * f64<&>").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.clone(), b);
+        assert_ne!(a, fn_program::expr("add(x q0) * f64<&>").unwrap());
+    }
+
+    #[test]
+    fn literal_display() {
+        assert_eq!(Literal::Int(3, BackendKind::QPU, None).to_string(), "@3");
+        assert_eq!(Literal::Bool(true, BackendKind::CPU, None).to_string(), "true");
+        assert_eq!(Literal::Float(1.0, BackendKind::CPU, None).to_string(), "1.0");
+        assert_eq!(Literal::Float(0.1 + 0.2, BackendKind::CPU, None).to_string(), "0.30000000000000004");
+        let s = Literal::Str(String::from("a\"b"), BackendKind::CPU, None);
+        assert_eq!(s.to_string(), "\"a\\\"b\"");
+        let s = Literal::Str(String::from("a\nb\\"), BackendKind::CPU, None);
+        assert_eq!(s.to_string(), "\"a\\nb\\\\\"");
+    }
+
+    #[test]
+    fn expr_display_round_trip() {
+        for code in [
+            "add(1 2)",
+            "@h(@q0)",
+            "run(q)<shots=1000>",
+            "add(a b) * f64<&>",
+            "p.x.y",
+            "loop(i){ return i }",
+            "match(x:{ return 1 } y:{})",
+        ] {
+            let expr = fn_program::expr(code).unwrap();
+            assert_eq!(expr.to_string(), code);
+        }
+    }
+
+    #[test]
+    fn stmt_display_round_trip() {
+        for code in ["x: i32", "<mut> x: i32 = add(1 2)", "return x"] {
+            let stmt = fn_program::stmt(code).unwrap();
+            assert_eq!(stmt.to_string(), code);
+        }
+    }
+
+    #[test]
+    fn symbol_hashing() {
+        let mut set = HashSet::new();
+        set.insert(Symbol::new(String::from("x"), BackendKind::CPU));
+        set.insert(Symbol::new(String::from("x"), BackendKind::QPU));
+        set.insert(Symbol::with_span(String::from("x"), BackendKind::CPU, (0, 1)));
+        assert_eq!(set.len(), 2);
+
+        let path = |kind| CompositeSymbol::new(vec![
+            Symbol::new(String::from("color"), BackendKind::CPU),
+            Symbol::new(String::from("red"), kind),
+        ]);
+        let paths: HashSet<CompositeSymbol> = [path(BackendKind::CPU), path(BackendKind::CPU)].into();
+        assert_eq!(paths.len(), 1);
+        assert_ne!(path(BackendKind::CPU), path(BackendKind::QPU));
+    }
+
+    #[test]
+    fn block_iteration() {
+        let block = fn_program::block("{ return a; b }").unwrap();
+        assert_eq!(block.len(), 2);
+        assert!(!block.is_empty());
+        assert_eq!(block.iter().count(), 2);
+        let mut n = 0;
+        for _ in &block {
+            n += 1;
+        }
+        assert_eq!(n, 2);
+        assert!(Block::new(vec![]).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn content_to_json() {
+        let (_, content) = fn_program::module("fn main() null { @h(@q0) return add(1 2) }").unwrap();
+        let json = crate::ir::hir::to_json(&content).unwrap();
+        assert!(json.contains(r#""value":"main""#));
+        assert!(json.contains(r#""value":"h","backend_kind":"QPU""#));
+        assert!(json.contains(r#""value":"add","backend_kind":"CPU""#));
+    }
+}
@@ -1,8 +1,28 @@
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use std::str::FromStr;
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
 /// Use this for naming, such as module paths.
+///
+/// Paths with the same segments are equal and hash alike, so they can key
+/// module maps. It derefs to its segments.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Path(Vec<String>);
 
+impl Deref for Path {
+    type Target = [String];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 impl Display for Path {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0.join("."))
@@ -10,15 +30,70 @@ impl Display for Path {
 }
 
 impl Path {
+    pub fn new(segments: Vec<String>) -> Self {
+        Self(segments)
+    }
+
+    /// Path from a dotted string, ex: `"a.b.c"`. An empty string gives an empty path.
+    pub fn from_dotted(s: &str) -> Self {
+        if s.is_empty() {
+            return Self(vec![]);
+        }
+        Self(s.split('.').map(String::from).collect())
+    }
+
+    pub fn push(&mut self, seg: String) {
+        self.0.push(seg);
+    }
+
+    pub fn segments(&self) -> &[String] {
+        &self.0
+    }
+
     pub fn len(&self) -> usize {
         self.0.len()
     }
 
+    pub fn iter(&self) -> std::slice::Iter<'_, String> {
+        self.0.iter()
+    }
 }
 
 #[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SymbolId(pub u32);
 
+/// String interner for symbols.
+///
+/// Each distinct string gets a single [`SymbolId`], so symbols
+/// can be compared by id instead of by their string values.
+///
+#[derive(Default)]
+pub struct Interner {
+    ids: HashMap<String, SymbolId>,
+    names: Vec<String>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&mut self, s: &str) -> SymbolId {
+        if let Some(id) = self.ids.get(s) {
+            return *id;
+        }
+        let id = SymbolId(self.names.len() as u32);
+        self.names.push(String::from(s));
+        self.ids.insert(String::from(s), id);
+        id
+    }
+
+    pub fn resolve(&self, id: SymbolId) -> Option<&str> {
+        self.names.get(id.0 as usize).map(String::as_str)
+    }
+}
+
 #[repr(transparent)]
 pub struct LiteralId(pub u32);
 
@@ -26,8 +101,15 @@ pub struct LiteralId(pub u32);
 pub struct ExprId(pub u32);
 
 #[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ModuleId(pub u32);
 
+impl ModuleId {
+    pub fn new(id: u32) -> Self {
+        Self(id)
+    }
+}
+
 
 /// Computational backend kind.
 ///
@@ -39,29 +121,263 @@ pub struct ModuleId(pub u32);
 /// Existing enumerated backend kinds:
 /// - CPU
 /// - QPU  (lazy mode only)
+/// - GPU
+/// - NPU
+/// - TPU
 ///
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum BackendKind {
     CPU,
     /// QPUs can only execute on lazy mode.
     QPU,
+    GPU,
+    NPU,
+    TPU,
 }
 
 impl BackendKind {
+    /// Every backend kind, in declaration order.
+    pub fn all() -> [BackendKind; 5] {
+        [BackendKind::CPU, BackendKind::QPU, BackendKind::GPU, BackendKind::NPU, BackendKind::TPU]
+    }
+
+    /// Canonical name, ex: `"CPU"`, as parsed by its [`FromStr`] impl.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackendKind::CPU => "CPU",
+            BackendKind::QPU => "QPU",
+            BackendKind::GPU => "GPU",
+            BackendKind::NPU => "NPU",
+            BackendKind::TPU => "TPU",
+        }
+    }
+
     pub fn sugar_fmt(&self) -> String {
         match self {
             BackendKind::CPU => String::from(""),
             BackendKind::QPU => String::from("@"),
+            BackendKind::GPU => String::from("+"),
+            BackendKind::NPU => String::from("!"),
+            BackendKind::TPU => String::from("%"),
         }
     }
     pub fn sugar_str(&self) -> &str {
         match self {
             BackendKind::CPU => "",
             BackendKind::QPU => "@",
+            BackendKind::GPU => "+",
+            BackendKind::NPU => "!",
+            BackendKind::TPU => "%",
+        }
+    }
+
+    /// Whether the backend can execute instructions immediately (strict mode).
+    pub fn supports_strict(&self) -> bool {
+        match self {
+            BackendKind::QPU => false,
+            BackendKind::CPU
+            | BackendKind::GPU
+            | BackendKind::NPU
+            | BackendKind::TPU => true,
         }
-    } 
+    }
+
+    /// Whether the backend can execute instructions lazily (lazy mode).
+    pub fn supports_lazy(&self) -> bool {
+        true
+    }
+
+    /// Inverse of [`BackendKind::sugar_str`] for a single prefix character.
+    /// CPU has no sugar, so it is never returned here.
+    pub fn from_sugar_char(c: char) -> Option<BackendKind> {
+        match c {
+            '@' => Some(BackendKind::QPU),
+            '+' => Some(BackendKind::GPU),
+            '!' => Some(BackendKind::NPU),
+            '%' => Some(BackendKind::TPU),
+            _ => None,
+        }
+    }
 }
 
+/// Backend kind from its long name (`"CPU"`, `"qpu"`, etc.), case-insensitive.
+impl FromStr for BackendKind {
+    type Err = String;
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BackendKind::all()
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names = BackendKind::all().map(|kind| kind.name().to_ascii_lowercase());
+                format!("unknown backend `{}`, expected one of: {}", s, names.join(", "))
+            })
+    }
+}
+
+
+/// Symbol table as a stack of scopes.
+///
+/// Lookups go from the innermost (last pushed) scope to the outermost,
+/// so inner bindings shadow outer ones until their scope is popped.
+///
 pub struct SymbolContext {
+    scopes: Vec<HashMap<String, SymbolId>>,
+}
+
+impl Default for SymbolContext {
+    fn default() -> Self {
+        Self { scopes: vec![HashMap::new()] }
+    }
+}
+
+impl SymbolContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Bind `name` in the innermost scope.
+    pub fn insert(&mut self, name: &str, id: SymbolId) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(String::from(name), id);
+        }
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<SymbolId> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name).copied())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::ir::ids::{BackendKind, Interner, Path, SymbolContext, SymbolId};
 
+    #[test]
+    fn backend_from_sugar_char() {
+        assert_eq!(BackendKind::from_sugar_char('@'), Some(BackendKind::QPU));
+        assert_eq!(BackendKind::from_sugar_char('+'), Some(BackendKind::GPU));
+        assert_eq!(BackendKind::from_sugar_char('!'), Some(BackendKind::NPU));
+        assert_eq!(BackendKind::from_sugar_char('%'), Some(BackendKind::TPU));
+        assert_eq!(BackendKind::from_sugar_char('a'), None);
+        assert_eq!(BackendKind::from_sugar_char('$'), None);
+    }
+
+    #[test]
+    fn backend_execution_modes() {
+        let table = [
+            (BackendKind::CPU, true, true),
+            (BackendKind::QPU, false, true),
+            (BackendKind::GPU, true, true),
+            (BackendKind::NPU, true, true),
+            (BackendKind::TPU, true, true),
+        ];
+        for (kind, strict, lazy) in table {
+            assert_eq!(kind.supports_strict(), strict, "{:?} strict", kind);
+            assert_eq!(kind.supports_lazy(), lazy, "{:?} lazy", kind);
+        }
+    }
+
+    #[test]
+    fn backend_from_str() {
+        assert_eq!("CPU".parse(), Ok(BackendKind::CPU));
+        assert_eq!("qpu".parse(), Ok(BackendKind::QPU));
+        assert_eq!("Gpu".parse(), Ok(BackendKind::GPU));
+        assert_eq!("npu".parse(), Ok(BackendKind::NPU));
+        assert_eq!("TPU".parse(), Ok(BackendKind::TPU));
+        assert_eq!(
+            "xpu".parse::<BackendKind>(),
+            Err(String::from("unknown backend `xpu`, expected one of: cpu, qpu, gpu, npu, tpu"))
+        );
+        assert!("".parse::<BackendKind>().is_err());
+    }
+
+    #[test]
+    fn interner_dedup() {
+        let mut interner = Interner::new();
+        let a = interner.intern("a");
+        let b = interner.intern("b");
+        assert_ne!(a, b);
+        assert_eq!(interner.intern("a"), a);
+        assert_eq!(interner.intern("b"), b);
+    }
+
+    #[test]
+    fn interner_resolve() {
+        let mut interner = Interner::new();
+        let id = interner.intern("q0");
+        assert_eq!(interner.resolve(id), Some("q0"));
+        assert_eq!(interner.resolve(SymbolId(42)), None);
+    }
+
+    #[test]
+    fn path_from_dotted() {
+        let path = Path::from_dotted("a.b.c");
+        assert_eq!(path.segments(), ["a", "b", "c"]);
+        assert_eq!(path.to_string(), "a.b.c");
+        assert_eq!(Path::from_dotted(&path.to_string()).segments(), path.segments());
+
+        let mut path = Path::from_dotted("");
+        assert_eq!(path.len(), 0);
+        assert_eq!(path.to_string(), "");
+        path.push(String::from("x"));
+        assert_eq!(Path::new(vec![String::from("x")]).segments(), path.segments());
+    }
+
+    #[test]
+    fn symbol_context_shadowing() {
+        let mut ctx = SymbolContext::new();
+        ctx.insert("x", SymbolId(0));
+        ctx.push_scope();
+        assert_eq!(ctx.lookup("x"), Some(SymbolId(0)));
+        ctx.insert("x", SymbolId(1));
+        assert_eq!(ctx.lookup("x"), Some(SymbolId(1)));
+        ctx.pop_scope();
+        assert_eq!(ctx.lookup("x"), Some(SymbolId(0)));
+    }
+
+    #[test]
+    fn symbol_context_popped_scope() {
+        let mut ctx = SymbolContext::new();
+        ctx.push_scope();
+        ctx.insert("y", SymbolId(3));
+        assert_eq!(ctx.lookup("y"), Some(SymbolId(3)));
+        ctx.pop_scope();
+        assert_eq!(ctx.lookup("y"), None);
+    }
+
+    #[test]
+    fn path_as_map_key() {
+        let mut modules = HashMap::new();
+        modules.insert(Path::from_dotted("geometry.point"), 1);
+        let same = Path::new(vec![String::from("geometry"), String::from("point")]);
+        assert_eq!(modules.get(&same), Some(&1));
+        modules.insert(same, 2);
+        assert_eq!(modules.len(), 1);
+
+        let path = Path::from_dotted("a.b");
+        assert_eq!(path.iter().map(String::as_str).collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(path.last().map(String::as_str), Some("b"));
+    }
+
+    #[test]
+    fn backend_names_round_trip() {
+        let all = BackendKind::all();
+        assert_eq!(all.len(), 5);
+        for kind in all {
+            assert_eq!(kind.name().parse(), Ok(kind));
+        }
+        assert_eq!(BackendKind::QPU.name(), "QPU");
+    }
 }
@@ -1,10 +1,11 @@
-mod project;
+pub(crate) mod project;
 mod lazy;
 mod meta;
 mod modifier;
 mod layout_ir;
 mod types;
-mod ids;
-mod hir;
-mod modules;
+pub(crate) mod ids;
+pub(crate) mod hir;
+pub(crate) mod visit;
+pub(crate) mod modules;
 mod mir;
\ No newline at end of file
@@ -1,6 +1,9 @@
-use std::env;
+use std::path::Component;
 use crate::ir::ids::Path;
 use crate::ir::hir::{Content, Imports};
+use crate::ir::project::SourceModule;
+use crate::parse::parser::parse_source;
+use crate::utils::errors::ModuleError;
 
 /// module for HIR.
 /// First module produced for the HIR.
@@ -14,13 +17,38 @@ pub struct HIRModule {
 
 
 impl HIRModule {
-    pub fn new(path: String) -> Self {
-        todo!()
+    /// Read and parse the module at `path`, picking the grammar from the
+    /// kind of items it defines (constants, types or groups).
+    ///
+    /// The module is named after its path relative to the project `root`,
+    /// without the extension, ex: `geometry/point.hat` -> `geometry.point`.
+    pub fn new(path: String, root: &std::path::Path) -> Result<Self, ModuleError> {
+        HIRModule::from_source(&SourceModule::new(&path)?, root)
     }
 
-    fn string_to_vec(path_str: &String) -> Vec<String> {
+    /// Parse an already loaded module, see [`HIRModule::new`].
+    pub fn from_source(source: &SourceModule, root: &std::path::Path) -> Result<Self, ModuleError> {
+        let (imports, content) = parse_source(&source.raw_code)?;
+        Ok(Self { name: source.relative_name(root), imports, content })
+    }
+
+    /// Dotted name of a module path, without the extension.
+    pub(crate) fn module_name(path: &std::path::Path) -> Path {
+        let dotted = path
+            .with_extension("")
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(s) => s.to_str(),
+                _ => None,
+            })
+            .collect::<Vec<&str>>()
+            .join(".");
+        Path::from_dotted(&dotted)
+    }
+
+    fn string_to_vec(path_str: &str) -> Vec<String> {
         let x: Vec<String> = path_str.split("/")
-            .filter_map(|x| Some(String::from(x)))
+            .map(String::from)
             .collect();
         x
     }
@@ -30,7 +58,9 @@ impl HIRModule {
 
 #[cfg(test)]
 mod tests {
+    use crate::ir::hir::{Content, Imports, TypeDef};
     use crate::ir::modules::HIRModule;
+    use crate::utils::errors::ModuleError;
 
     #[test]
     fn split_string() {
@@ -39,4 +69,34 @@ mod tests {
         println!("{:?}", res);
 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn hir_module_from_file() {
+        let module = HIRModule::new(
+            String::from("tests/fixtures/hir-module/geometry/point.hat"),
+            "tests/fixtures/hir-module".as_ref(),
+        ).unwrap();
+        assert_eq!(module.name.segments(), ["geometry", "point"]);
+        assert!(matches!(&module.imports[..], [Imports::Types(paths)] if paths.len() == 1));
+        let Content::Types(types) = &module.content else {
+            panic!("expected types content");
+        };
+        assert!(matches!(&types[..], [TypeDef::StructDef(s)] if s.name.value == "point"));
+    }
+
+    #[test]
+    fn hir_module_errors() {
+        assert!(matches!(
+            HIRModule::new(String::from("tests/fixtures/missing.hat"), "tests/fixtures".as_ref()),
+            Err(ModuleError::ModuleNotFound)
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.hat");
+        std::fs::write(&path, "fn main( null {}").unwrap();
+        assert!(matches!(
+            HIRModule::new(path.to_str().unwrap().to_string(), dir.path()),
+            Err(ModuleError::ParseFailed { .. })
+        ));
+    }
+}
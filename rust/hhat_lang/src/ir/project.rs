@@ -3,12 +3,18 @@
 //! The objects appearance order reflects its position on the compilation steps.
 //!
 
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{Display, Formatter};
-use std::fs::read_to_string;
-use std::path::PathBuf;
+use std::fs::read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use glob::Pattern;
+use rayon::prelude::*;
 use walkdir::WalkDir;
-use crate::ir::ids::ModuleId;
-use crate::utils::errors::ModuleError;
+use crate::ir::hir::Imports;
+use crate::ir::ids::{self, ModuleId};
+use crate::ir::modules::HIRModule;
+use crate::utils::errors::{ModuleError, ProjectError};
 
 
 /// Raw code's project object.
@@ -16,47 +22,295 @@ use crate::utils::errors::ModuleError;
 /// adding to a vector of module sources.
 ///
 /// It is the very first step on the project compilation pipeline.
+///
+/// Each module gets a [`ModuleId`] in the order of its (sorted) path.
+///
+/// Files that could be read but aren't valid UTF-8, or whose path isn't,
+/// are skipped, and their errors kept in `errors`.
 #[derive(Debug)]
 pub struct SourceProject {
     pub root: PathBuf,
     pub sources: Vec<SourceModule>,
+    pub errors: Vec<ModuleError>,
+    module_ids: HashMap<PathBuf, ModuleId>,
 }
 
 impl SourceProject {
-    pub fn new(root_path: &str) -> Self {
-        Self {
-            root:PathBuf::from(root_path),
-            sources: SourceProject::get_modules(root_path)
+    /// Load the project at `root_path` with the default [`Discovery`].
+    pub fn new(root_path: &str) -> Result<Self, ProjectError> {
+        SourceProject::load(root_path, &Discovery::default())
+    }
+
+    /// Fails with [`ProjectError::RootNotADirectory`] if there's no directory
+    /// at `root_path`, and with [`ProjectError::NoModulesFound`] if no module
+    /// was discovered under it.
+    pub fn load(root_path: &str, discovery: &Discovery) -> Result<Self, ProjectError> {
+        if !Path::new(root_path).is_dir() {
+            return Err(ProjectError::RootNotADirectory(PathBuf::from(root_path)));
+        }
+        let (sources, errors) = SourceProject::get_modules(root_path, discovery)?;
+        if sources.is_empty() && errors.is_empty() {
+            return Err(ProjectError::NoModulesFound);
         }
+        let mut project = SourceProject::from_modules(PathBuf::from(root_path), sources);
+        project.errors = errors;
+        Ok(project)
+    }
+
+    /// Build a project from in-memory `(path, code)` pairs, without touching
+    /// the filesystem (e.g. unsaved editor buffers). Unlike
+    /// [`SourceProject::new`], an empty project is allowed here.
+    pub fn from_sources(root: PathBuf, sources: Vec<(PathBuf, String)>) -> Self {
+        let sources = sources.into_iter()
+            .map(|(path, raw_code)| SourceModule { path, raw_code })
+            .collect();
+        SourceProject::from_modules(root, sources)
+    }
+
+    /// Modules are sorted by their full path so the project is deterministic
+    /// regardless of the order they were found or read in.
+    fn from_modules(root: PathBuf, mut sources: Vec<SourceModule>) -> Self {
+        sources.sort_by(|a, b| a.path.as_os_str().cmp(b.path.as_os_str()));
+        let module_ids = sources.iter()
+            .enumerate()
+            .map(|(n, m)| (m.path.clone(), ModuleId::new(n as u32)))
+            .collect();
+        Self { root, sources, errors: vec![], module_ids }
+    }
+
+    pub fn module_id(&self, path: &Path) -> Option<ModuleId> {
+        self.module_ids.get(path).copied()
     }
 
     pub fn is_empty(&self) -> bool {
         self.sources.is_empty()
     }
 
-    fn get_modules(path: &str) -> Vec<SourceModule> {
-        SourceProject::get_files(path).into_iter()
-            .filter_map(|f| {
-                Some(SourceModule::new(
-                    f.to_str()
-                        .expect("could not get module source path from {path}"))
-                )
+    /// Load every module found on `path` in parallel, failing if any of
+    /// them fails. Modules with an invalid encoding or a non-UTF-8 path are
+    /// returned apart instead.
+    fn get_modules(
+        path: &str,
+        discovery: &Discovery,
+    ) -> Result<(Vec<SourceModule>, Vec<ModuleError>), ModuleError> {
+        let files = SourceProject::get_files(Path::new(path), discovery);
+        let results: Vec<Result<SourceModule, ModuleError>> = files.par_iter()
+            .map(|f| {
+                f.to_str()
+                    .ok_or_else(|| ModuleError::InvalidPath { path: f.clone() })
+                    .and_then(SourceModule::new)
             })
-            .collect::<Vec<SourceModule>>()
+            .collect();
+        let mut sources = vec![];
+        let mut errors = vec![];
+        for result in results {
+            match result {
+                Ok(module) => sources.push(module),
+                Err(err @ (ModuleError::InvalidEncoding { .. } | ModuleError::InvalidPath { .. })) => {
+                    errors.push(err)
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok((sources, errors))
     }
 
-    fn get_files(path: &str) -> Vec<PathBuf> {
-        WalkDir::new(path).into_iter()
+    fn get_files(path: &Path, discovery: &Discovery) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = WalkDir::new(path)
+            .follow_links(discovery.follow)
+            .into_iter()
+            // walkdir reports symlink loops as errors, skipping them
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
             .filter(|e|
                 e.path()
                     .extension()
                     .and_then(|x| x.to_str())
-                    .is_some_and(|x| x.eq("hat"))
+                    .is_some_and(|x| discovery.exts.iter().any(|ext| x.eq_ignore_ascii_case(ext)))
             )
+            .filter(|e| {
+                let rel = e.path().strip_prefix(path).unwrap_or(e.path());
+                !discovery.excludes.iter().any(|p| p.matches_path(rel))
+            })
             .map(|e| e.into_path())
-            .collect()
+            .collect();
+        if discovery.follow {
+            files.sort_by(|a, b| a.as_os_str().cmp(b.as_os_str()));
+            let mut seen = HashSet::new();
+            files.retain(|f| seen.insert(f.canonicalize().unwrap_or_else(|_| f.clone())));
+        }
+        files
+    }
+}
+
+/// Which files are picked up as modules when walking a project's root.
+///
+/// By default, only `.hat` files are picked up, symlinks are not followed
+/// and no file is excluded:
+/// ```rust
+/// let discovery = Discovery::default()
+///     .with_extensions(&["hat", "hatx"])
+///     .with_symlinks(true)
+///     .with_excludes(&["**/generated/**"])?;
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Discovery {
+    exts: Vec<String>,
+    excludes: Vec<Pattern>,
+    follow: bool,
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        Self { exts: vec![String::from("hat")], excludes: vec![], follow: false }
+    }
+}
+
+impl Discovery {
+    /// Pick up files with any of the extensions in `exts` (case-insensitive),
+    /// instead of `.hat` only.
+    pub fn with_extensions(mut self, exts: &[&str]) -> Self {
+        self.exts = exts.iter().map(|ext| ext.to_string()).collect();
+        self
+    }
+
+    /// Follow symlinks when `follow` is set. Symlink loops are not walked
+    /// into, and a file reached through more than one link is only loaded once.
+    pub fn with_symlinks(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    /// Skip files matching any of the glob patterns in `excludes`, matched
+    /// against paths relative to the root.
+    pub fn with_excludes(mut self, excludes: &[&str]) -> Result<Self, ModuleError> {
+        for p in excludes {
+            let pattern = Pattern::new(p).map_err(|_| ModuleError::InvalidPattern(String::from(*p)))?;
+            self.excludes.push(pattern);
+        }
+        Ok(self)
+    }
+}
+
+/// Project loader for long-lived tools (e.g. editors), keeping each module
+/// and its parsed [`HIRModule`] along with its file's modification time.
+///
+/// On [`reload`](CachedProjectLoader::reload), only files whose modification
+/// time changed are read again; unchanged ones reuse the cached module. Files
+/// skipped for their encoding or path are cached too, so they aren't read
+/// again until modified.
+///
+pub struct CachedProjectLoader {
+    root: PathBuf,
+    discovery: Discovery,
+    cache: HashMap<PathBuf, CachedModule>,
+    reads: usize,
+}
+
+struct CachedModule {
+    modified: SystemTime,
+    entry: CachedEntry,
+}
+
+enum CachedEntry {
+    Loaded { source: SourceModule, parsed: Result<HIRModule, ModuleError> },
+    /// Not valid UTF-8, either the file or its path
+    Skipped(ModuleError),
+}
+
+impl CachedProjectLoader {
+    /// Load the project at `root_path` with the default [`Discovery`].
+    pub fn new(root_path: &str) -> Result<Self, ProjectError> {
+        CachedProjectLoader::load(root_path, Discovery::default())
+    }
+
+    /// Same as [`SourceProject::load`], keeping `discovery` for the reloads.
+    pub fn load(root_path: &str, discovery: Discovery) -> Result<Self, ProjectError> {
+        let mut loader = Self {
+            root: PathBuf::from(root_path),
+            discovery,
+            cache: HashMap::new(),
+            reads: 0,
+        };
+        loader.reload()?;
+        Ok(loader)
+    }
+
+    /// Sync the cache with the files currently under the root, reading only
+    /// new or modified ones and dropping the removed ones.
+    ///
+    /// Fails like [`SourceProject::load`]; the cache is left untouched then.
+    pub fn reload(&mut self) -> Result<(), ProjectError> {
+        if !self.root.is_dir() {
+            return Err(ProjectError::RootNotADirectory(self.root.clone()));
+        }
+        let files = SourceProject::get_files(&self.root, &self.discovery);
+        let mut cache = HashMap::with_capacity(files.len());
+        let mut unchanged = vec![];
+        for file in files {
+            let modified = file.metadata()
+                .and_then(|m| m.modified())
+                .map_err(|_| ModuleError::CannotReadFile)?;
+            if self.cache.get(&file).is_some_and(|c| c.modified == modified) {
+                unchanged.push(file);
+                continue;
+            }
+            let entry = match self.read(&file) {
+                Ok(source) => {
+                    let parsed = HIRModule::from_source(&source, &self.root);
+                    CachedEntry::Loaded { source, parsed }
+                }
+                Err(err @ (ModuleError::InvalidEncoding { .. } | ModuleError::InvalidPath { .. })) => {
+                    CachedEntry::Skipped(err)
+                }
+                Err(err) => return Err(err.into()),
+            };
+            cache.insert(file, CachedModule { modified, entry });
+        }
+        if cache.is_empty() && unchanged.is_empty() {
+            return Err(ProjectError::NoModulesFound);
+        }
+        cache.extend(unchanged.iter().filter_map(|file| self.cache.remove_entry(file)));
+        self.cache = cache;
+        Ok(())
+    }
+
+    fn read(&mut self, file: &Path) -> Result<SourceModule, ModuleError> {
+        let path = file.to_str().ok_or_else(|| ModuleError::InvalidPath { path: file.to_path_buf() })?;
+        self.reads += 1;
+        SourceModule::new(path)
+    }
+
+    /// Project with the cached modules, as [`SourceProject::new`] would
+    /// build it.
+    pub fn project(&self) -> SourceProject {
+        let mut entries: Vec<_> = self.cache.iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.as_os_str().cmp(b.as_os_str()));
+        let mut sources = vec![];
+        let mut errors = vec![];
+        for (_, cached) in entries {
+            match &cached.entry {
+                CachedEntry::Loaded { source, .. } => sources.push(source.clone()),
+                CachedEntry::Skipped(err) => errors.push(err.clone()),
+            }
+        }
+        let mut project = SourceProject::from_modules(self.root.clone(), sources);
+        project.errors = errors;
+        project
+    }
+
+    /// Parsed module at `path`, if it was loaded.
+    pub fn module(&self, path: &Path) -> Option<Result<&HIRModule, &ModuleError>> {
+        match &self.cache.get(path)?.entry {
+            CachedEntry::Loaded { parsed, .. } => Some(parsed.as_ref()),
+            CachedEntry::Skipped(_) => None,
+        }
+    }
+
+    /// Number of files read from disk since the loader was created.
+    pub fn reads(&self) -> usize {
+        self.reads
     }
 }
 
@@ -74,34 +328,40 @@ impl Display for SourceProject {
 
 /// Raw code's module object.
 ///
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SourceModule {
     pub path: PathBuf,
     pub raw_code: String
 }
 
 impl SourceModule {
-    pub fn new(path: &str) -> Self {
-        if !path.is_empty() {
-            return match SourceModule::read_file(&path) {
-                Ok(raw_code) => {
-                    let path_buf = PathBuf::from(path);
-                    if path_buf.is_file() {
-                        Self { path: path_buf, raw_code }
-                    }
-                    else { panic!("\"{}\" is not a file to load module source", path) }
-                },
-                Err(err) => panic!("{} {}", err, path)
-            }
+    pub fn new(path: &str) -> Result<Self, ModuleError> {
+        if path.is_empty() {
+            return Err(ModuleError::EmptyPath);
         }
-        panic!("provided path for module source is empty.")
+        let path_buf = PathBuf::from(path);
+        if !path_buf.is_file() {
+            return Err(ModuleError::ModuleNotFound);
+        }
+        let raw_code = SourceModule::read_file(path)?;
+        Ok(Self { path: path_buf, raw_code })
+    }
+
+    /// Module name from its path relative to the project `root`, without
+    /// the extension, ex: `root/a/b/c.hat` -> `a.b.c`.
+    pub fn relative_name(&self, root: &Path) -> ids::Path {
+        HIRModule::module_name(self.path.strip_prefix(root).unwrap_or(&self.path))
     }
 
+    /// A leading UTF-8 BOM, as written by some editors, is stripped.
     fn read_file(path: &str) -> Result<String, ModuleError> {
-        match read_to_string(path) {
-            Ok(x) => Ok(x),
-            Err(_) => Err(ModuleError::CannotReadFile)
-        }
+        let bytes = read(path).map_err(|_| ModuleError::CannotReadFile)?;
+        let code = String::from_utf8(bytes)
+            .map_err(|_| ModuleError::InvalidEncoding { path: PathBuf::from(path) })?;
+        Ok(match code.strip_prefix('\u{FEFF}') {
+            Some(stripped) => stripped.to_string(),
+            None => code,
+        })
     }
 }
 
@@ -208,6 +468,98 @@ pub struct UnresolvedModule {
 }
 
 
+/// Import dependencies between modules.
+///
+/// An edge `from -> to` means module `from` imports from module `to`, so `to`
+/// must be compiled first.
+///
+#[derive(Debug, Default)]
+pub struct ModuleGraph {
+    edges: BTreeMap<ModuleId, BTreeSet<ModuleId>>,
+}
+
+impl ModuleGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Graph of the imports between the parsed `modules` of `project`: an
+    /// import such as `use fns geometry.dist` adds an edge to the module
+    /// named `geometry` (see [`SourceModule::relative_name`]). Imports from
+    /// modules outside the project are left out.
+    pub fn from_modules<'a>(
+        project: &SourceProject,
+        modules: impl IntoIterator<Item = &'a HIRModule>,
+    ) -> Self {
+        let ids: HashMap<ids::Path, ModuleId> = project.sources.iter()
+            .filter_map(|s| Some((s.relative_name(&project.root), project.module_id(&s.path)?)))
+            .collect();
+        let mut graph = ModuleGraph::new();
+        for module in modules {
+            let Some(from) = ids.get(&module.name) else {
+                continue;
+            };
+            graph.add_module(*from);
+            let deps = module.imports.iter()
+                .flat_map(Imports::paths)
+                .filter_map(|import| ids.get(&import.path));
+            for to in deps {
+                graph.add_edge(*from, *to);
+            }
+        }
+        graph
+    }
+
+    /// Add a module without dependencies, if not already in the graph.
+    pub fn add_module(&mut self, id: ModuleId) {
+        self.edges.entry(id).or_default();
+    }
+
+    pub fn add_edge(&mut self, from: ModuleId, to: ModuleId) {
+        self.edges.entry(from).or_default().insert(to);
+        self.add_module(to);
+    }
+
+    /// Modules ordered so every module comes after the ones it imports.
+    ///
+    /// Ties are broken by module id, so the order is deterministic. Fails
+    /// with [`ModuleError::ImportCycle`] listing the modules of the first
+    /// cycle found.
+    pub fn topo_order(&self) -> Result<Vec<ModuleId>, ModuleError> {
+        let mut order = Vec::with_capacity(self.edges.len());
+        let mut done = HashSet::new();
+        let mut stack = Vec::new();
+        for id in self.edges.keys() {
+            self.visit(*id, &mut done, &mut stack, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    fn visit(
+        &self,
+        id: ModuleId,
+        done: &mut HashSet<ModuleId>,
+        stack: &mut Vec<ModuleId>,
+        order: &mut Vec<ModuleId>,
+    ) -> Result<(), ModuleError> {
+        if done.contains(&id) {
+            return Ok(());
+        }
+        if let Some(pos) = stack.iter().position(|m| *m == id) {
+            return Err(ModuleError::ImportCycle { modules: stack[pos..].to_vec() });
+        }
+        stack.push(id);
+        for dep in self.edges.get(&id).into_iter().flatten() {
+            self.visit(*dep, done, stack, order)?;
+        }
+        stack.pop();
+        done.insert(id);
+        order.push(id);
+        Ok(())
+    }
+}
+
+
 /// Project object for the second compilation pass.
 /// Holds [`MappedModule`]s.
 ///
@@ -227,21 +579,357 @@ pub struct MappedModule {
 /// Tests for some sanity checks
 #[cfg(test)]
 mod tests {
+    use std::fs;
     use std::path::PathBuf;
-    use crate::ir::project::SourceProject;
+    use std::time::{Duration, SystemTime};
+    use crate::ir::hir::Content;
+    use crate::ir::ids::{ModuleId, Path};
+    use crate::ir::modules::HIRModule;
+    use crate::ir::project::{CachedProjectLoader, Discovery, ModuleGraph, SourceModule, SourceProject};
+    use crate::utils::errors::{ModuleError, ProjectError};
 
-    /// TODO: replace the python path for an independent place to retrieve
-    ///  hat code for debugging and checking purposes.
-    ///
     /// Common path containing hat files for test purposes.
-    const PATH: &str = "../../python/tests/dialects/heather/parsing/parse-test/";
+    const PATH: &str = "tests/fixtures/parse-test/";
 
     #[test]
     fn check_project_source() {
         assert!(PathBuf::from(PATH).exists());
-        let ps = SourceProject::new(PATH);
+        let ps = SourceProject::new(PATH).unwrap();
         println!("{}", ps);
         assert!(!ps.is_empty());
 
     }
+
+    #[test]
+    fn module_relative_name() {
+        let module = SourceModule { path: PathBuf::from("root/a/b/c.hat"), raw_code: String::new() };
+        assert_eq!(module.relative_name("root".as_ref()), Path::from_dotted("a.b.c"));
+        assert_eq!(module.relative_name("root/a/".as_ref()), Path::from_dotted("b.c"));
+    }
+
+    #[test]
+    fn project_sources_are_sorted() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in ["z.hat", "a.hat", "m/b.hat", "m/a.hat", "b/c.hat"] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        let ps = SourceProject::new(root.to_str().unwrap()).unwrap();
+        let paths: Vec<&PathBuf> = ps.sources.iter().map(|m| &m.path).collect();
+        let mut sorted = paths.clone();
+        sorted.sort_by(|a, b| a.as_os_str().cmp(b.as_os_str()));
+        assert_eq!(paths.len(), 5);
+        assert_eq!(paths, sorted);
+        assert!(paths[0].ends_with("a.hat"));
+    }
+
+    #[test]
+    fn parallel_read_matches_serial() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for n in 0..200 {
+            let path = root.join(format!("d{}/m{}.hat", n % 7, n));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, format!("fn f{}() null {{}}", n)).unwrap();
+        }
+        let root_str = root.to_str().unwrap();
+        let ps = SourceProject::new(root_str).unwrap();
+
+        let mut files = SourceProject::get_files(root, &Discovery::default());
+        files.sort_by(|a, b| a.as_os_str().cmp(b.as_os_str()));
+        let serial: Vec<SourceModule> = files.iter()
+            .map(|f| SourceModule::new(f.to_str().unwrap()).unwrap())
+            .collect();
+
+        assert_eq!(ps.sources.len(), 200);
+        assert_eq!(ps.sources.len(), serial.len());
+        for (parallel, serial) in ps.sources.iter().zip(&serial) {
+            assert_eq!(parallel.path, serial.path);
+            assert_eq!(parallel.raw_code, serial.raw_code);
+        }
+    }
+
+    #[test]
+    fn project_module_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in ["c.hat", "a.hat", "b/a.hat"] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        let ps = SourceProject::new(root.to_str().unwrap()).unwrap();
+        for (n, module) in ps.sources.iter().enumerate() {
+            assert_eq!(ps.module_id(&module.path), Some(ModuleId::new(n as u32)));
+        }
+        assert_eq!(ps.module_id(&root.join("a.hat")), Some(ModuleId::new(0)));
+        assert_eq!(ps.module_id(&root.join("c.hat")), Some(ModuleId::new(2)));
+        assert_eq!(ps.module_id(&root.join("missing.hat")), None);
+    }
+
+    #[test]
+    fn project_from_sources() {
+        let ps = SourceProject::from_sources(
+            PathBuf::from("mem"),
+            vec![
+                (PathBuf::from("mem/main.hat"), String::from("fn main() null {}")),
+                (PathBuf::from("mem/consts.hat"), String::from("const pi: f64")),
+            ],
+        );
+        assert!(!ps.is_empty());
+        assert_eq!(ps.sources.len(), 2);
+        assert_eq!(ps.module_id(&PathBuf::from("mem/consts.hat")), Some(ModuleId::new(0)));
+        assert_eq!(ps.sources[1].raw_code, "fn main() null {}");
+    }
+
+    #[test]
+    fn project_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in ["a.hat", "b.hatx", "c.HAT", "d.txt"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+        let root_str = root.to_str().unwrap();
+
+        let ps = SourceProject::new(root_str).unwrap();
+        assert_eq!(ps.sources.len(), 2);
+
+        let discovery = Discovery::default().with_extensions(&["hat", "hatx"]);
+        let ps = SourceProject::load(root_str, &discovery).unwrap();
+        let names: Vec<&str> = ps.sources.iter()
+            .map(|m| m.path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["a.hat", "b.hatx", "c.HAT"]);
+    }
+
+    #[test]
+    fn project_excludes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in ["main.hat", "build/out.hat", "build/gen/deep.hat", "src/lib.hat"] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        let root_str = root.to_str().unwrap();
+
+        let excludes = |patterns| Discovery::default().with_excludes(patterns);
+        let ps = SourceProject::load(root_str, &excludes(&["build/**"]).unwrap()).unwrap();
+        let paths: Vec<&PathBuf> = ps.sources.iter().map(|m| &m.path).collect();
+        assert_eq!(paths, vec![&root.join("main.hat"), &root.join("src/lib.hat")]);
+
+        let ps = SourceProject::load(root_str, &excludes(&["**/gen/**"]).unwrap()).unwrap();
+        assert_eq!(ps.sources.len(), 3);
+
+        assert!(matches!(excludes(&["a/***"]), Err(ModuleError::InvalidPattern(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn project_symlink_loop() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.hat"), "").unwrap();
+        symlink(root, root.join("loop")).unwrap();
+        symlink(root.join("a.hat"), root.join("b.hat")).unwrap();
+        let root_str = root.to_str().unwrap();
+
+        let ps = SourceProject::new(root_str).unwrap();
+        let paths: Vec<&PathBuf> = ps.sources.iter().map(|m| &m.path).collect();
+        assert_eq!(paths, vec![&root.join("a.hat")]);
+
+        let ps = SourceProject::load(root_str, &Discovery::default().with_symlinks(true)).unwrap();
+        let paths: Vec<&PathBuf> = ps.sources.iter().map(|m| &m.path).collect();
+        assert_eq!(paths, vec![&root.join("a.hat")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn project_symlinks_and_excludes() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let (root, shared) = (dir.path().join("root"), dir.path().join("shared"));
+        fs::create_dir_all(root.join("gen")).unwrap();
+        fs::create_dir_all(&shared).unwrap();
+        fs::write(root.join("main.hat"), "").unwrap();
+        fs::write(root.join("gen/out.hatx"), "").unwrap();
+        fs::write(shared.join("lib.hatx"), "").unwrap();
+        symlink(&shared, root.join("shared")).unwrap();
+
+        let discovery = Discovery::default()
+            .with_extensions(&["hat", "hatx"])
+            .with_symlinks(true)
+            .with_excludes(&["gen/**"])
+            .unwrap();
+        let ps = SourceProject::load(root.to_str().unwrap(), &discovery).unwrap();
+        let paths: Vec<&PathBuf> = ps.sources.iter().map(|m| &m.path).collect();
+        assert_eq!(paths, vec![&root.join("main.hat"), &root.join("shared/lib.hatx")]);
+
+        let loader = CachedProjectLoader::load(root.to_str().unwrap(), discovery).unwrap();
+        assert_eq!(loader.project().sources.len(), 2);
+    }
+
+    #[test]
+    fn module_graph_linear_chain() {
+        let [a, b, c] = [0, 1, 2].map(ModuleId::new);
+        let mut graph = ModuleGraph::new();
+        graph.add_edge(a, b);
+        graph.add_edge(b, c);
+        assert_eq!(graph.topo_order().unwrap(), vec![c, b, a]);
+    }
+
+    #[test]
+    fn module_graph_cycle() {
+        let [a, b, c] = [0, 1, 2].map(ModuleId::new);
+        let mut graph = ModuleGraph::new();
+        graph.add_module(c);
+        graph.add_edge(a, b);
+        graph.add_edge(b, a);
+        let Err(ModuleError::ImportCycle { modules }) = graph.topo_order() else {
+            panic!("expected an import cycle");
+        };
+        assert_eq!(modules, vec![a, b]);
+    }
+
+    #[test]
+    fn module_graph_from_imports() {
+        let project = SourceProject::from_sources(PathBuf::from("root"), vec![
+            (PathBuf::from("root/geo/point.hat"), String::from("use fns main.run\nfn dist() null {}")),
+            (PathBuf::from("root/main.hat"), String::from("use fns geo.point.dist io.print\nfn run() null {}")),
+        ]);
+        let modules: Vec<HIRModule> = project.sources.iter()
+            .map(|s| HIRModule::from_source(s, &project.root).unwrap())
+            .collect();
+        let graph = ModuleGraph::from_modules(&project, &modules);
+        let Err(ModuleError::ImportCycle { modules }) = graph.topo_order() else {
+            panic!("expected an import cycle");
+        };
+        assert_eq!(modules, vec![ModuleId::new(0), ModuleId::new(1)]);
+    }
+
+    #[test]
+    fn project_root_errors() {
+        let missing = "tests/fixtures/does-not-exist";
+        assert!(matches!(
+            SourceProject::new(missing),
+            Err(ProjectError::RootNotADirectory(p)) if p == std::path::Path::new(missing)
+        ));
+        assert!(matches!(
+            SourceProject::new("tests/fixtures/parse-test/src/main.hat"),
+            Err(ProjectError::RootNotADirectory(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_encoding_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("good.hat"), "fn main() null { }").unwrap();
+        fs::write(dir.path().join("bad.hat"), [0x66, 0x6e, 0xff, 0xfe]).unwrap();
+        let project = SourceProject::new(dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(project.sources.len(), 1);
+        assert!(project.sources[0].path.ends_with("good.hat"));
+        assert!(matches!(
+            &project.errors[..],
+            [ModuleError::InvalidEncoding { path }] if path.ends_with("bad.hat")
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_path_is_skipped() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("good.hat"), "fn main() null { }").unwrap();
+        let bad = dir.path().join(std::ffi::OsStr::from_bytes(b"bad\xff.hat"));
+        fs::write(&bad, "fn f() null { }").unwrap();
+        let project = SourceProject::new(dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(project.sources.len(), 1);
+        assert!(matches!(&project.errors[..], [ModuleError::InvalidPath { path }] if *path == bad));
+    }
+
+    #[test]
+    fn leading_bom_is_stripped() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("bom.hat");
+        fs::write(&file, "\u{FEFF}fn main() null { \"\u{FEFF}\" }").unwrap();
+        let module = SourceModule::new(file.to_str().unwrap()).unwrap();
+        assert!(module.raw_code.starts_with("fn main()"));
+        assert!(module.raw_code.contains('\u{FEFF}'));
+    }
+
+    #[test]
+    fn cached_loader_reads_modified_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.hat");
+        let b = dir.path().join("b.hat");
+        fs::write(&a, "fn a() null {}").unwrap();
+        fs::write(&b, "fn b() null {}").unwrap();
+
+        let mut loader = CachedProjectLoader::new(dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(loader.reads(), 2);
+        loader.reload().unwrap();
+        assert_eq!(loader.reads(), 2);
+
+        fs::write(&b, "fn b2() null {}").unwrap();
+        let later = SystemTime::now() + Duration::from_secs(10);
+        fs::File::options().write(true).open(&b).unwrap().set_modified(later).unwrap();
+        loader.reload().unwrap();
+        assert_eq!(loader.reads(), 3);
+
+        let project = loader.project();
+        assert_eq!(project.sources.len(), 2);
+        assert_eq!(project.sources[1].raw_code, "fn b2() null {}");
+
+        fs::remove_file(&a).unwrap();
+        loader.reload().unwrap();
+        assert_eq!(loader.project().sources.len(), 1);
+        assert_eq!(loader.reads(), 3);
+        let module = loader.module(&b).unwrap().unwrap();
+        assert!(matches!(&module.content, Content::Groups(groups) if groups.len() == 1));
+        assert_eq!(module.name, Path::from_dotted("b"));
+
+        fs::remove_file(&b).unwrap();
+        assert!(matches!(loader.reload(), Err(ProjectError::NoModulesFound)));
+        assert_eq!(loader.project().sources.len(), 1);
+    }
+
+    #[test]
+    fn cached_loader_keeps_skipped_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.hat"), "fn a() null {}").unwrap();
+        fs::write(dir.path().join("bad.hat"), b"fn \xff() null {}").unwrap();
+
+        let mut loader = CachedProjectLoader::new(dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(loader.reads(), 2);
+        loader.reload().unwrap();
+        assert_eq!(loader.reads(), 2);
+        assert!(matches!(loader.project().errors[..], [ModuleError::InvalidEncoding { .. }]));
+    }
+
+    #[test]
+    fn empty_project() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+        let root = dir.path().to_str().unwrap();
+        assert!(matches!(SourceProject::new(root), Err(ProjectError::NoModulesFound)));
+
+        let project = SourceProject::from_sources(PathBuf::from(root), vec![]);
+        assert!(project.is_empty());
+    }
+
+    #[test]
+    fn module_source_errors() {
+        assert!(matches!(SourceModule::new(""), Err(ModuleError::EmptyPath)));
+        assert!(matches!(
+            SourceModule::new("tests/fixtures/does-not-exist.hat"),
+            Err(ModuleError::ModuleNotFound)
+        ));
+        assert!(matches!(SourceModule::new(PATH), Err(ModuleError::ModuleNotFound)));
+    }
 }
\ No newline at end of file
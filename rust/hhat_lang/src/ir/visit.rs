@@ -0,0 +1,142 @@
+//! Visitor over HIR expressions.
+//!
+//! Implementors override the hooks they care about; the default hooks recurse
+//! into children through the `walk_*` functions, which overriding hooks can
+//! call to keep the traversal going.
+//!
+
+use crate::ir::hir::{Assign, Block, CompositeSymbol, EnumMembersInit, Expr, Literal, MetaCall, Stmt, Symbol, TypeName};
+
+
+pub trait ExprVisitor {
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_id(&mut self, _sym: &Symbol) {}
+
+    fn visit_literal(&mut self, _lit: &Literal) {}
+
+    /// Function call, `callee(args)`.
+    fn visit_call(&mut self, _callee: &Symbol, args: &[Expr]) {
+        for arg in args {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_meta_call(&mut self, call: &MetaCall) {
+        walk_meta_call(self, call)
+    }
+
+    fn visit_cast(&mut self, value: &Expr, _to_ty: &TypeName) {
+        self.visit_expr(value)
+    }
+
+    fn visit_member_access(&mut self, _composite: &CompositeSymbol) {}
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        walk_block(self, block)
+    }
+}
+
+
+/// Dispatch `expr` to the visitor hook for its kind.
+pub fn walk_expr<V: ExprVisitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Id(sym) => visitor.visit_id(sym),
+        Expr::Literal(lit) => visitor.visit_literal(lit),
+        Expr::Call { callee, args, .. } => visitor.visit_call(callee, args),
+        Expr::MetaCall(call) => visitor.visit_meta_call(call),
+        Expr::Cast { value, to_ty, .. } => visitor.visit_cast(value, to_ty),
+        Expr::DataMemberAccess(composite) => visitor.visit_member_access(composite),
+    }
+}
+
+/// Visit the arguments, options and bodies of a meta-function call.
+pub fn walk_meta_call<V: ExprVisitor + ?Sized>(visitor: &mut V, call: &MetaCall) {
+    match call {
+        MetaCall::Optn { options, .. } => {
+            for option in options {
+                visitor.visit_expr(&option.opt);
+                visitor.visit_block(&option.body);
+            }
+        }
+        MetaCall::Bdn { args, body, .. } => {
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+            visitor.visit_block(body);
+        }
+        MetaCall::OptBdn { args, body, .. } => {
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+            for option in body {
+                visitor.visit_expr(&option.opt);
+                visitor.visit_block(&option.body);
+            }
+        }
+    }
+}
+
+/// Visit the expressions held by a statement.
+pub fn walk_stmt<V: ExprVisitor + ?Sized>(visitor: &mut V, stmt: &Stmt) {
+    match stmt {
+        Stmt::Declare { .. } | Stmt::Assign(Assign::Enum { members: EnumMembersInit::Kind(_), .. }) => {}
+        Stmt::Assign(Assign::Struct { members, .. })
+        | Stmt::Assign(Assign::Enum { members: EnumMembersInit::Struct(members), .. }) => {
+            for member in members {
+                visitor.visit_expr(&member.value);
+            }
+        }
+        Stmt::DeclareAssign { value, .. }
+        | Stmt::Assign(Assign::Single { value, .. })
+        | Stmt::Expr(value)
+        | Stmt::Return(value) => visitor.visit_expr(value),
+    }
+}
+
+pub fn walk_block<V: ExprVisitor + ?Sized>(visitor: &mut V, block: &Block) {
+    for stmt in block {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::Symbol;
+    use crate::ir::visit::ExprVisitor;
+    use crate::parse::parser::fn_program;
+
+    #[derive(Default)]
+    struct IdCounter {
+        ids: usize,
+    }
+
+    impl ExprVisitor for IdCounter {
+        fn visit_id(&mut self, _sym: &Symbol) {
+            self.ids += 1;
+        }
+    }
+
+    #[test]
+    fn count_ids_in_nested_call() {
+        let expr = fn_program::expr("add(a mul(b 2) sub(c neg(d)) * f64)").unwrap();
+        let mut counter = IdCounter::default();
+        counter.visit_expr(&expr);
+        assert_eq!(counter.ids, 4);
+    }
+
+    #[test]
+    fn count_ids_in_meta_call_body() {
+        let expr = fn_program::expr("loop(i){ x: i32 = add(i j) return x }").unwrap();
+        let mut counter = IdCounter::default();
+        counter.visit_expr(&expr);
+        assert_eq!(counter.ids, 4);
+    }
+}
@@ -0,0 +1,178 @@
+//! JIT compilation of CPU functions with Cranelift.
+//!
+//! Only integer functions are supported for now: `i64` parameters, the
+//! arithmetic built-ins (`add`, `sub`, `mul`, `div`) over them and integer
+//! literals, with declarations and a single `return`.
+//!
+
+use std::collections::HashMap;
+use cranelift::codegen::isa::CallConv;
+use cranelift::prelude::*;
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, Linkage, Module};
+use crate::ir::hir::{Expr, FnDef, Literal, Stmt};
+use crate::ir::ids::BackendKind;
+use crate::utils::errors::BackendError;
+
+
+/// Compiles [`FnDef`]s to native code.
+///
+/// Compiled functions live as long as the compiler that produced them.
+///
+pub struct JitCompiler {
+    module: JITModule,
+    builder_ctx: FunctionBuilderContext,
+}
+
+impl JitCompiler {
+    pub fn new() -> Result<Self, BackendError> {
+        let builder = JITBuilder::new(default_libcall_names())
+            .map_err(|e| BackendError::LoweringFailed(e.to_string()))?;
+        Ok(Self { module: JITModule::new(builder), builder_ctx: FunctionBuilderContext::new() })
+    }
+
+    /// Compile a function taking two `i64` and returning an `i64`, with the
+    /// platform's C calling convention.
+    ///
+    /// The returned function is `unsafe` to call: an integer division by zero
+    /// or overflowing (`i64::MIN / -1`) traps, aborting the process.
+    pub fn compile(&mut self, fn_def: &FnDef) -> Result<unsafe extern "C" fn(i64, i64) -> i64, BackendError> {
+        if fn_def.params.len() != 2
+            || fn_def.params.iter().any(|p| !is_i64(&p.ty.name.value))
+            || !is_i64(&fn_def.ty.name.value)
+        {
+            return Err(BackendError::UnsupportedConstruct {
+                construct: format!("signature of `{}`", fn_def.name),
+            });
+        }
+
+        let mut ctx = self.module.make_context();
+        ctx.func.signature.call_conv = CallConv::triple_default(self.module.isa().triple());
+        for _ in &fn_def.params {
+            ctx.func.signature.params.push(AbiParam::new(types::I64));
+        }
+        ctx.func.signature.returns.push(AbiParam::new(types::I64));
+
+        let mut builder = FunctionBuilder::new(&mut ctx.func, &mut self.builder_ctx);
+        let entry = builder.create_block();
+        builder.append_block_params_for_function_params(entry);
+        builder.switch_to_block(entry);
+        builder.seal_block(entry);
+
+        let mut lowering = FnLowering { builder, vars: HashMap::new() };
+        for (param, value) in fn_def.params.iter().zip(lowering.builder.block_params(entry).to_vec()) {
+            lowering.vars.insert(param.name.to_string(), value);
+        }
+        let res = lowering.lower_body(fn_def);
+        lowering.builder.finalize();
+        res?;
+
+        let id = self.module
+            .declare_function(&fn_def.name.value, Linkage::Export, &ctx.func.signature)
+            .map_err(|e| BackendError::LoweringFailed(e.to_string()))?;
+        self.module
+            .define_function(id, &mut ctx)
+            .map_err(|e| BackendError::LoweringFailed(e.to_string()))?;
+        self.module.clear_context(&mut ctx);
+        self.module
+            .finalize_definitions()
+            .map_err(|e| BackendError::LoweringFailed(e.to_string()))?;
+
+        let code = self.module.get_finalized_function(id);
+        // SAFETY: the function was declared with the (i64, i64) -> i64 signature
+        // above, using the platform's C calling convention
+        Ok(unsafe { std::mem::transmute::<*const u8, unsafe extern "C" fn(i64, i64) -> i64>(code) })
+    }
+}
+
+fn is_i64(ty: &str) -> bool {
+    ty == "i64"
+}
+
+
+struct FnLowering<'a> {
+    builder: FunctionBuilder<'a>,
+    vars: HashMap<String, Value>,
+}
+
+impl FnLowering<'_> {
+    fn lower_body(&mut self, fn_def: &FnDef) -> Result<(), BackendError> {
+        for stmt in &fn_def.body {
+            match stmt {
+                Stmt::DeclareAssign { name, ty, value, .. } if is_i64(&ty.name.value) => {
+                    let value = self.lower_expr(value)?;
+                    self.vars.insert(name.to_string(), value);
+                }
+                Stmt::Return(expr) => {
+                    let value = self.lower_expr(expr)?;
+                    self.builder.ins().return_(&[value]);
+                    return Ok(());
+                }
+                other => {
+                    return Err(BackendError::UnsupportedConstruct { construct: other.to_string() });
+                }
+            }
+        }
+        Err(BackendError::LoweringFailed(format!("`{}` has no return", fn_def.name)))
+    }
+
+    fn lower_expr(&mut self, expr: &Expr) -> Result<Value, BackendError> {
+        match expr {
+            Expr::Literal(Literal::Int(v, BackendKind::CPU, _)) => {
+                Ok(self.builder.ins().iconst(types::I64, *v))
+            }
+            Expr::Id(sym) => self.vars
+                .get(&sym.to_string())
+                .copied()
+                .ok_or_else(|| BackendError::LoweringFailed(format!("undefined `{}`", sym))),
+            Expr::Call { callee, args, .. } if args.len() == 2 => {
+                let lhs = self.lower_expr(&args[0])?;
+                let rhs = self.lower_expr(&args[1])?;
+                let ins = self.builder.ins();
+                match callee.to_string().as_str() {
+                    "add" => Ok(ins.iadd(lhs, rhs)),
+                    "sub" => Ok(ins.isub(lhs, rhs)),
+                    "mul" => Ok(ins.imul(lhs, rhs)),
+                    "div" => Ok(ins.sdiv(lhs, rhs)),
+                    _ => Err(BackendError::UnsupportedConstruct { construct: expr.to_string() }),
+                }
+            }
+            _ => Err(BackendError::UnsupportedConstruct { construct: expr.to_string() }),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::jit::compiler::JitCompiler;
+    use crate::parse::parser::fn_program;
+    use crate::utils::errors::BackendError;
+
+    #[test]
+    fn jit_add() {
+        let fn_def = fn_program::fn_def("fn add(a:i64 b:i64) i64 { return add(a b) }").unwrap();
+        let mut jit = JitCompiler::new().unwrap();
+        let add = jit.compile(&fn_def).unwrap();
+        assert_eq!(unsafe { add(2, 3) }, 5);
+    }
+
+    #[test]
+    fn jit_declarations_and_literals() {
+        let code = "fn f(a:i64 b:i64) i64 { c: i64 = mul(a 10) return sub(div(c b) 1) }";
+        let mut jit = JitCompiler::new().unwrap();
+        let f = jit.compile(&fn_program::fn_def(code).unwrap()).unwrap();
+        assert_eq!(unsafe { f(6, 4) }, 14);
+    }
+
+    #[test]
+    fn jit_unsupported() {
+        let mut jit = JitCompiler::new().unwrap();
+        let code = "fn f(a:i64 b:i64) i64 { return add(a 1.5) }";
+        let res = jit.compile(&fn_program::fn_def(code).unwrap());
+        assert!(matches!(res, Err(BackendError::UnsupportedConstruct { construct }) if construct == "1.5"));
+        let code = "fn g(a:f64 b:i64) i64 { return b }";
+        let res = jit.compile(&fn_program::fn_def(code).unwrap());
+        assert!(matches!(res, Err(BackendError::UnsupportedConstruct { .. })));
+    }
+}
@@ -0,0 +1,2 @@
+#[cfg(feature = "jit")]
+pub(crate) mod compiler;
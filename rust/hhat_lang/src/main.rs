@@ -1,7 +1,6 @@
 #![allow(dead_code, unused)]
 
 use std::collections::HashMap;
-use peg;
 
 mod ir;
 mod parse;
@@ -15,7 +14,7 @@ mod semantics;
 mod utils;
 mod subcompilers;
 
-fn main() {
-
+fn main() -> std::process::ExitCode {
+    toolchain::cli::run()
 }
 
@@ -2,35 +2,1122 @@
 //!
 
 use peg;
+use crate::ir::hir::{
+    Assign, Block, CompositeSymbol, ConstDef, Content, ContentKind, EnumMember, Expr, FnDef,
+    GroupsDef, ImportPathSymbol, Imports, Literal, MetaCall, MetaFnDef, Modifier, ModifierDef,
+    OptionBody, Param, Stmt, StructDef, StructMember, StructMembersInit, Symbol, TypeDef, TypeName,
+};
+use crate::ir::ids::{BackendKind, Path};
+use crate::passes::validate::validate_content;
+use crate::utils::errors::ModuleError;
 
 
-peg::parser!(
-    /// Function grammar
-    pub grammar fn_program() for str {
-        pub rule whitespace() -> String
-            = w:[' ' | '\t' | '\n' | ';' | ',']* { w.into_iter().collect() }
+/// Reserved words, which cannot be used as identifiers.
+pub const KEYWORDS: &[&str] = &[
+    "fn", "const", "struct", "enum", "return", "use", "cast", "metafn", "modifier",
+];
+
+
+/// Parse the digits of an integer literal in the given base, with its sign.
+fn parse_int(sign: &str, digits: &str, radix: u32) -> Result<Literal, &'static str> {
+    i64::from_str_radix(&format!("{}{}", sign, digits.replace('_', "")), radix)
+        .map(|v| Literal::Int(v, BackendKind::CPU, None))
+        .or(Err("i64 literal"))
+}
+
+
+/// Expands each grammar listed after the `common` block into its own
+/// `peg::parser!` grammar, with the common rules included in every one of them.
+macro_rules! hat_grammars {
+    (common $common:tt $($(#[$($attr:tt)*])* $name:ident $rules:tt)*) => {
+        $(hat_grammars!(@grammar $common $(#[$($attr)*])* $name $rules);)*
+    };
+    (@grammar { $($common:tt)* } $(#[$($attr:tt)*])* $name:ident { $($rules:tt)* }) => {
+        peg::parser!(
+            $(#[$($attr)*])*
+            pub grammar $name() for str {
+                $($common)*
+                $($rules)*
+            }
+        );
+    };
+}
+
+
+hat_grammars! {
+    // Rules shared by all grammars: whitespace, comments, symbols,
+    // literals, modifiers and expressions.
+    common {
+        /// Spaces, newlines and comments, all of which are ignored
+        pub rule whitespace()
+            = quiet!{ ([' ' | '\t' | '\n' | '\r'] / comment())* }
 
+        /// Separator between statements: spaces, newlines, `;` and comments
+        pub rule stmt_sep()
+            = quiet!{ ([' ' | '\t' | '\n' | '\r' | ';'] / comment())* }
+
+        /// Separator between arguments and other list items: spaces, newlines,
+        /// `,` and comments
+        pub rule arg_sep()
+            = quiet!{ ([' ' | '\t' | '\n' | '\r' | ','] / comment())* }
+
+        /// Line comment (`# note`) or nestable block comment (`#{ note }#`)
+        pub rule comment()
+            = block_comment()
+            / "#" (!"\n" [_])*
+
+        rule block_comment()
+            = "#{" (block_comment() / !"}#" [_])* "}#"
+
+        rule ident_char()
+            = ['a'..='z'|'A'..='Z'|'0'..='9'|'_'|'-']
+
+        /// Identifier, other than one of the [`KEYWORDS`]
         pub rule vals() -> String
-            = v:$(['a'..='z'|'A'..='Z']['a'..='z'|'A'..='Z'|'0'..='9']*) { v.to_owned() }
+            = v:$(['a'..='z'|'A'..='Z']['a'..='z'|'A'..='Z'|'0'..='9'|'_'|'-']*) {?
+                match KEYWORDS.contains(&v) {
+                    true => Err("identifier"),
+                    false => Ok(v.to_owned()),
+                }
+            }
+
+        /// Backend sugar prefix, ex: `@` for QPU; no prefix means CPU
+        pub rule backend_prefix() -> BackendKind
+            = "@" { BackendKind::QPU }
+            / "+" { BackendKind::GPU }
+            / "!" { BackendKind::NPU }
+            / "%" { BackendKind::TPU }
+            / "" { BackendKind::CPU }
+
+        /// Identifier with its backend prefix, ex: `x`, `@q0`
+        pub rule symbol() -> Symbol
+            = start:position!() b:backend_prefix() v:vals() end:position!() {
+                Symbol::with_span(v, b, (start, end))
+            }
+
+        rule modifier_name() -> Symbol
+            = symbol()
+            / start:position!() "&" end:position!() {
+                Symbol::with_span(String::from("&"), BackendKind::CPU, (start, end))
+            }
+
+        /// No-arg modifier, ex: `<mut>`, `<&>`, or
+        /// single-arg modifier, ex: `<shots=1000>`, `<device=qiskit.aer-sim>`
+        pub rule modifier() -> Modifier
+            = "<" name:modifier_name() value:("=" v:expr() { v })? ">" {
+                Modifier { name, value }
+            }
+
+        /// Zero or more consecutive modifiers, ex: `<mut><&>`
+        pub rule modifiers() -> Vec<Modifier>
+            = modifier() ** whitespace()
+
+        /// Integer literal as a signed 64-bit value, in decimal or with a base
+        /// prefix, ex: `42`, `-7`, `0xFF`, `0b1010`, `0o17`, `1_000_000`
+        pub rule int_literal() -> Literal
+            = s:$("-"?) "0x" d:$((['0'..='9' | 'a'..='f' | 'A'..='F']+) ++ "_") !ident_char() {? parse_int(s, d, 16) }
+            / s:$("-"?) "0b" d:$((['0' | '1']+) ++ "_") !ident_char() {? parse_int(s, d, 2) }
+            / s:$("-"?) "0o" d:$((['0'..='7']+) ++ "_") !ident_char() {? parse_int(s, d, 8) }
+            / s:$("-"?) d:decimal() !ident_char() {? parse_int(s, d, 10) }
+
+        /// Decimal digits, with single `_` separators between them, ex: `1_000`
+        rule decimal() -> &'input str
+            = $((['0'..='9']+) ++ "_")
+
+        rule exponent()
+            = ['e' | 'E'] ['+' | '-']? decimal()
+
+        /// Float literal as a 64-bit value, ex: `3.14`, `6.022e23`, `3.141_592`
+        pub rule float_literal() -> Literal
+            = n:$("-"? decimal() ("." decimal()? exponent()? / exponent()))
+              !['a'..='z' | 'A'..='Z' | '0'..='9' | '_'] {?
+                n.replace('_', "").parse::<f64>()
+                    .map(|v| Literal::Float(v, BackendKind::CPU, None))
+                    .or(Err("f64 literal"))
+            }
+
+        /// String literal with its backend prefix, ex: `"a\nb"`, `@"text"`
+        pub rule string_literal() -> Literal
+            = b:backend_prefix() "\"" c:string_char()* "\"" {
+                Literal::Str(c.into_iter().collect(), b, None)
+            }
+
+        /// Raw string literal, taken verbatim and possibly spanning lines, ex: `r"a\nb"`,
+        /// `r#"say "hi""#`; it ends at the first `"` followed by as many `#` as it started with
+        pub rule raw_string_literal() -> Literal
+            = b:backend_prefix() "r" h:$("#"*) "\"" c:$((!raw_end(h.len()) [_])*) raw_end(h.len()) {
+                Literal::Str(c.to_string(), b, None)
+            }
+
+        rule raw_end(hashes: usize)
+            = "\"" "#"*<{hashes}>
+
+        rule string_char() -> char
+            = "\\" e:escape() { e }
+            / [^ '"' | '\\']
+
+        /// Escape sequence after a `\`: `\n`, `\r`, `\t`, `\\`, `\"` or `\u{1F600}`
+        rule escape() -> char
+            = "n" { '\n' }
+            / "r" { '\r' }
+            / "t" { '\t' }
+            / "\\" { '\\' }
+            / "\"" { '"' }
+            / "u{" h:$(['0'..='9' | 'a'..='f' | 'A'..='F']*<1,6>) "}" {?
+                u32::from_str_radix(h, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or("unicode scalar value")
+            }
+            / expected!("escape sequence")
+
+        /// Dotted member access, ex: `var.member1.member2`, or the brace form
+        /// `var.{member1 member2}`, whose members follow the base symbols in
+        /// the resulting [`CompositeSymbol`]. A bare symbol falls through to
+        /// [`Expr::Id`].
+        pub rule member_access() -> Expr
+            = s:(symbol() ++ ".")
+              m:("." "{" arg_sep() m:(symbol() ** arg_sep()) arg_sep() "}" { m })? {
+                let mut value = s;
+                value.extend(m.unwrap_or_default());
+                if value.len() == 1 {
+                    Expr::Id(value.remove(0))
+                } else {
+                    Expr::DataMemberAccess(CompositeSymbol::new(value))
+                }
+            }
+
+        /// Type name with its backend prefix and leading modifiers, ex: `i32`,
+        /// `<mut> i32`, `@qubit`
+        pub rule type_name() -> TypeName
+            = modifiers:modifiers() whitespace() name:symbol() { TypeName { name, modifiers } }
+
+        /// Function call, ex: `add(1 2)`, `run(q)<shots=1000>`
+        pub rule call() -> Expr
+            = callee:symbol() "(" arg_sep() args:(expr() ** arg_sep()) arg_sep() ")"
+              modifiers:modifiers() {
+                Expr::Call { callee, args, modifiers }
+            }
+
+        /// Option with its body, ex: `x:{ return 1 }`
+        pub rule option_body() -> OptionBody
+            = opt:expr() whitespace() ":" whitespace() body:block() { OptionBody { opt, body } }
+
+        /// Option (cases) meta-function call, ex: `match(x:{ return 1 } y:{ return 2 })`
+        pub rule optn_call() -> Expr
+            = name:symbol() "(" arg_sep() options:(option_body() ++ arg_sep()) arg_sep() ")"
+              modifiers:modifiers() {
+                Expr::MetaCall(MetaCall::Optn { name, options, modifiers })
+            }
+
+        /// Body (blocks) meta-function call, ex: `loop(i){ return i }`
+        pub rule bdn_call() -> Expr
+            = name:symbol() "(" arg_sep() args:(expr() ** arg_sep()) arg_sep() ")"
+              modifiers:modifiers() whitespace() body:block() {
+                Expr::MetaCall(MetaCall::Bdn { name, args, body, modifiers })
+            }
+
+        /// Option-body (case-blocks) meta-function call, ex:
+        /// `switch(x){ 1:{ return a } 2:{ return b } }`
+        pub rule optbdn_call() -> Expr
+            = name:symbol() "(" arg_sep() args:(expr() ** arg_sep()) arg_sep() ")"
+              modifiers:modifiers() whitespace()
+              "{" arg_sep() body:(option_body() ++ arg_sep()) arg_sep() "}" {
+                Expr::MetaCall(MetaCall::OptBdn { name, args, body, modifiers })
+            }
+
+        /// Any literal, with its span, ex: `3.14`, `42`, `"text"`
+        pub rule literal() -> Literal
+            = start:position!() l:(float_literal() / int_literal() / raw_string_literal() / string_literal()) end:position!() {
+                l.with_span((start, end))
+            }
+
+        rule primary() -> Expr
+            = l:literal() { Expr::Literal(l) }
+            / optn_call()
+            / optbdn_call()
+            / bdn_call()
+            / call()
+            / member_access()
+
+        /// Cast sugar `data * type`, ex: `v1 * u64`, `add(a b) * f64<&>`
+        rule cast_to() -> (TypeName, Vec<Modifier>)
+            = whitespace() "*" whitespace() ty:type_name() m:modifiers() { (ty, m) }
+
+        pub rule expr() -> Expr
+            = value:primary() casts:cast_to()* {
+                casts.into_iter().fold(value, |value, (to_ty, modifiers)| Expr::Cast {
+                    value: Box::new(value),
+                    to_ty: Box::new(to_ty),
+                    modifiers,
+                })
+            }
+
+        /// Parameter, ex: `a:i32`, `<mut> b:f64`
+        pub rule param() -> Param
+            = modifiers:modifiers() whitespace() name:symbol() whitespace() ":" whitespace()
+              ty:type_name() {
+                Param { name, ty, modifiers }
+            }
+
+        /// Parenthesized parameter list, ex: `(a:i32 <mut> b:f64)`, `()`
+        pub rule params() -> Vec<Param>
+            = "(" arg_sep() p:(param() ** arg_sep()) arg_sep() ")" { p }
+
+        /// Return statement, ex: `return x`
+        pub rule return_stmt() -> Stmt
+            = "return" !ident_char() whitespace() e:expr() { Stmt::Return(e) }
+
+        /// Declaration, ex: `x: i32`, optionally assigned, ex: `<mut> x: i32 = 42`
+        pub rule declare_stmt() -> Stmt
+            = modifiers:modifiers() whitespace() name:symbol() whitespace() ":" whitespace()
+              ty:type_name() value:(whitespace() "=" whitespace() e:expr() { e })? {
+                match value {
+                    Some(value) => Stmt::DeclareAssign { name, ty, modifiers, value },
+                    None => Stmt::Declare { name, ty, modifiers },
+                }
+            }
+
+        /// Struct member initialization, ex: `x:1`
+        rule member_init() -> StructMembersInit
+            = name:symbol() whitespace() ":" whitespace() value:expr() {
+                StructMembersInit::new(name, value)
+            }
+
+        /// Struct initialization, ex: `p = point{ x:1 y:2 }`
+        pub rule struct_assign() -> Stmt
+            = name:symbol() whitespace() "=" whitespace() ty:symbol()? whitespace()
+              "{" arg_sep() members:(member_init() ** arg_sep()) arg_sep() "}" {
+                Stmt::Assign(Assign::Struct { name, ty, members })
+            }
+
+        /// Reassignment of a declared variable, ex: `x = add(1 2)`, `<mut> x = 1`
+        pub rule assign_stmt() -> Stmt
+            = modifiers:modifiers() whitespace() name:symbol() whitespace() "=" whitespace() value:expr() {
+                Stmt::Assign(Assign::Single { name, value, modifiers })
+            }
+
+        pub rule stmt() -> Stmt
+            = return_stmt()
+            / declare_stmt()
+            / struct_assign()
+            / assign_stmt()
+            / e:expr() { Stmt::Expr(e) }
+
+        /// Block of statements, ex: `{ return a }`
+        pub rule block() -> Block
+            = "{" stmt_sep() s:(stmt() ** stmt_sep()) stmt_sep() "}" { Block::new(s) }
+
+        /// Import path with its final symbol, ex: `a.b.c`; only the final
+        /// symbol may have a backend prefix, ex: `quantum.gates.@h`
+        pub rule import_path() -> ImportPathSymbol
+            = !("use" !ident_char()) !item_kind() path:(v:vals() "." { v })* name:symbol() {
+                ImportPathSymbol::new(name, Path::new(path))
+            }
+
+        /// Import statement, ex: `use fns a.b.cfn d.efn`, `use types a.b.T`
+        pub rule import() -> Imports
+            = "use" !ident_char() whitespace() kind:$("consts" / "types" / "fns" / "modifiers" / "metafns")
+              !ident_char() whitespace() paths:(import_path() ++ arg_sep()) {
+                match kind {
+                    "consts" => Imports::Consts(paths),
+                    "types" => Imports::Types(paths),
+                    "fns" => Imports::Fns(paths),
+                    "modifiers" => Imports::Modifiers(paths),
+                    _ => Imports::MetaFns(paths),
+                }
+            }
+
+        /// Import lines, merged into at most one entry per kind
+        pub rule imports() -> Vec<Imports>
+            = i:(import() ** whitespace()) {
+                i.into_iter().fold(vec![], |mut imports, new| {
+                    Imports::merge(&mut imports, new);
+                    imports
+                })
+            }
 
-        pub rule start() -> Vec<String>
-            = "[" l:(vals() ** whitespace()) "]" { l }
+        /// Category of a top-level item, given by its leading keyword
+        rule item_kind() -> ContentKind
+            = "const" !ident_char() { ContentKind::Consts }
+            / ("struct" / "enum") !ident_char() { ContentKind::Types }
+            / ("fn" / "metafn" / "modifier") !ident_char() { ContentKind::Groups }
+
+        rule item_start() -> ContentKind
+            = modifiers() whitespace() k:item_kind() { k }
+
+        rule braced()
+            = "{" (braced() / raw_string_literal() / string_literal() / [^ '{' | '}'])* "}"
+
+        /// Category of each top-level item in a file, skipping over the items' content
+        pub rule item_kinds() -> Vec<ContentKind>
+            = whitespace() imports() whitespace()
+              kinds:(k:item_start() (!item_start() (braced() / vals() / [^ '{' | '}']))* { k })* {
+                kinds
+            }
     }
 
-);
+    /// Function grammar
+    fn_program {
+        /// Function definition, ex: `fn add(a:i32 b:i32) i32 { return a }`
+        pub rule fn_def() -> FnDef
+            = modifiers:modifiers() whitespace() "fn" !ident_char() whitespace() name:fn_name() whitespace()
+              params:params() whitespace()
+              ty:type_name() whitespace() body:block() {
+                FnDef { name, params, ty, modifiers, body }
+            }
 
+        /// Function name: a symbol, or the reserved `cast` for cast functions
+        rule fn_name() -> Symbol
+            = symbol()
+            / start:position!() b:backend_prefix() "cast" !ident_char() end:position!() {
+                Symbol::with_span(String::from("cast"), b, (start, end))
+            }
 
-peg::parser!(
-    /// Type grammar
-    pub grammar type_program() for str {
+        /// Meta-function definition, ex: `metafn twice(f:fn_t) { f() }`
+        pub rule metafn_def() -> MetaFnDef
+            = modifiers:modifiers() whitespace() "metafn" !ident_char() whitespace() name:symbol()
+              whitespace() params:params() whitespace() body:block() {
+                MetaFnDef { name, params, modifiers, body }
+            }
+
+        /// Modifier definition with up to two parameters, ex: `modifier shots(n:u32) { n }`
+        pub rule modifier_def() -> ModifierDef
+            = modifiers:modifiers() whitespace() "modifier" !ident_char() whitespace() name:symbol()
+              whitespace() params:params() whitespace() body:block() {?
+                if params.len() > 2 {
+                    return Err("at most two modifier parameters");
+                }
+                let mut params = params.into_iter();
+                Ok(ModifierDef { name, params: [params.next(), params.next()], modifiers, body })
+            }
+
+        /// Any definition allowed in a functions file
+        rule group_def() -> GroupsDef
+            = f:fn_def() { GroupsDef::FnDef(f) }
+            / m:metafn_def() { GroupsDef::MetaFnDef(m) }
+            / m:modifier_def() { GroupsDef::ModifierDef(m) }
 
+        pub rule start() -> Vec<Expr>
+            = "[" arg_sep() l:(expr() ** arg_sep()) arg_sep() "]" { l }
+
+        /// Functions file: imports followed by function definitions
+        pub rule module() -> (Vec<Imports>, Content)
+            = whitespace() i:imports() whitespace() g:(group_def() ** whitespace()) whitespace() {
+                (i, Content::Groups(g))
+            }
     }
-);
 
+    /// Type grammar
+    type_program {
+        rule struct_member() -> StructMember
+            = name:symbol() whitespace() ":" whitespace() ty:type_name() {
+                StructMember { name, ty }
+            }
+
+        rule struct_body() -> Vec<StructMember>
+            = "{" arg_sep() members:(struct_member() ** arg_sep()) arg_sep() "}" {
+                members
+            }
+
+        /// Struct definition, ex: `struct point { x:i32 y:i32 }`
+        pub rule struct_def() -> StructDef
+            = "struct" !ident_char() whitespace() name:symbol() whitespace() members:struct_body() {
+                StructDef { name, members, modifiers: vec![] }
+            }
+
+        /// Enum member, either a struct (`rgb{r:u8 g:u8 b:u8}`) when the name
+        /// is followed by a brace, or a single value (`ON`)
+        rule enum_member() -> EnumMember
+            = name:symbol() whitespace() members:struct_body() {
+                EnumMember::StructMember(StructDef { name, members, modifiers: vec![] })
+            }
+            / name:symbol() { EnumMember::KindMember(name) }
+
+        /// Enum definition, ex: `enum status { ON OFF }`
+        pub rule enum_def() -> TypeDef
+            = "enum" !ident_char() whitespace() name:symbol() whitespace()
+              "{" arg_sep() members:(enum_member() ** arg_sep()) arg_sep() "}" {
+                TypeDef::EnumDef { name, members, modifiers: vec![] }
+            }
+
+        rule type_def() -> TypeDef
+            = s:struct_def() { TypeDef::StructDef(s) }
+            / enum_def()
+
+        /// Types file: imports followed by struct and enum definitions
+        pub rule module() -> (Vec<Imports>, Content)
+            = whitespace() i:imports() whitespace() t:(type_def() ** whitespace()) whitespace() {
+                (i, Content::Types(t))
+            }
+    }
 
-peg::parser!(
     /// Const grammar
-    pub grammar const_program() for str {
+    const_program {
+        /// Constant definition, ex: `const PI: f64`, `<&> const PI: f64`
+        pub rule const_def() -> ConstDef
+            = modifiers:modifiers() whitespace() "const" !ident_char() whitespace() name:symbol()
+              whitespace() ":" whitespace() ty:type_name() {
+                ConstDef { name, ty, modifiers }
+            }
+
+        /// Constants file content
+        pub rule consts() -> Content
+            = whitespace() c:(const_def() ** whitespace()) whitespace() { Content::Consts(c) }
+
+        /// Constants file: imports followed by constant definitions
+        pub rule module() -> (Vec<Imports>, Content)
+            = whitespace() i:imports() whitespace() c:consts() { (i, c) }
+    }
+}
+
+
+/// Parse a module's source with the grammar for the kind of items it defines,
+/// given by their leading keyword: `const`, `struct`/`enum`, or
+/// `fn`/`metafn`/`modifier`.
+///
+/// Fails with [`ModuleError::EmptyContent`] if there is no item, and with
+/// [`ModuleError::MixedContent`] if items of different kinds are mixed.
+///
+pub fn parse_module(source: &str) -> Result<Content, ModuleError> {
+    parse_source(source).map(|(_, content)| content)
+}
+
+/// Same as [`parse_module`], keeping the module's imports.
+pub fn parse_source(source: &str) -> Result<(Vec<Imports>, Content), ModuleError> {
+    Ok(match validate_content(source)? {
+        ContentKind::Consts => const_program::module(source)?,
+        ContentKind::Types => type_program::module(source)?,
+        ContentKind::Groups => fn_program::module(source)?,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::{
+        Assign, Content, EnumMember, Expr, GroupsDef, Imports, Literal, MetaCall, Stmt, Symbol, TypeDef,
+    };
+    use crate::ir::ids::{BackendKind, Path};
+    use crate::parse::parser::{const_program, fn_program, parse_module, type_program};
+    use crate::utils::errors::ModuleError;
+
+    fn int_value(code: &str) -> i64 {
+        match fn_program::int_literal(code).unwrap() {
+            Literal::Int(v, _, _) => v,
+            _ => panic!("expected an int literal from {code}"),
+        }
+    }
+
+    #[test]
+    fn parse_int_literal() {
+        assert_eq!(int_value("0"), 0);
+        assert_eq!(int_value("-7"), -7);
+        assert_eq!(int_value("9223372036854775807"), i64::MAX);
+        assert!(fn_program::int_literal("9223372036854775808").is_err());
+    }
+
+    #[test]
+    fn parse_prefixed_int_literal() {
+        assert_eq!(int_value("0xFF"), 255);
+        assert_eq!(int_value("0xff"), 255);
+        assert_eq!(int_value("0b1010"), 10);
+        assert_eq!(int_value("0o17"), 15);
+        assert_eq!(int_value("-0x10"), -16);
+        assert_eq!(int_value("-0x8000000000000000"), i64::MIN);
+        assert!(fn_program::int_literal("0b12").is_err());
+        assert!(fn_program::int_literal("0o8").is_err());
+        assert!(fn_program::int_literal("0xG").is_err());
+        assert!(fn_program::expr("0b12").is_err());
+    }
+
+    fn string_value(code: &str) -> (String, BackendKind) {
+        match fn_program::string_literal(code).unwrap() {
+            Literal::Str(v, kind, _) => (v, kind),
+            _ => panic!("expected a string literal from {code}"),
+        }
+    }
+
+    #[test]
+    fn parse_string_literal() {
+        assert_eq!(string_value(r#""a\nb""#), (String::from("a\nb"), BackendKind::CPU));
+        assert_eq!(string_value(r#""\u{1F600}""#).0, "\u{1F600}");
+        assert_eq!(string_value(r#""t\t\\ \"q\"""#).0, "t\t\\ \"q\"");
+        let lit = fn_program::literal(r#""a\r\nb""#).unwrap();
+        assert_eq!(lit.to_string(), r#""a\r\nb""#);
+        assert_eq!(fn_program::literal(&lit.to_string()).unwrap(), lit);
+        assert_eq!(string_value(r#"@"text""#), (String::from("text"), BackendKind::QPU));
+        let expr = fn_program::expr(r#"print("hoi quantum")"#).unwrap();
+        assert_eq!(expr.to_string(), r#"print("hoi quantum")"#);
+    }
+
+    #[test]
+    fn parse_raw_string_literal() {
+        let raw = |code| match fn_program::raw_string_literal(code).unwrap() {
+            Literal::Str(v, _, _) => v,
+            _ => panic!("expected a string literal from {code}"),
+        };
+        assert_eq!(raw(r#"r"a\nb""#), "a\\nb");
+        assert_eq!(raw(r##"r#"say "hi""#"##), r#"say "hi""#);
+        assert_eq!(raw("r\"OPENQASM 3;\nqubit q;\""), "OPENQASM 3;\nqubit q;");
+        assert!(fn_program::raw_string_literal(r##"r#"say "hi""##).is_err());
+        let expr = fn_program::expr(r#"print(r"\d")"#).unwrap();
+        assert_eq!(expr.to_string(), r#"print("\\d")"#);
+    }
+
+    #[test]
+    fn parse_string_literal_errors() {
+        let err = fn_program::string_literal(r#""abc"#).unwrap_err();
+        assert_eq!(err.location.offset, 4);
+        assert!(err.expected.tokens().any(|t| t == r#""\"""#));
+        let err = fn_program::string_literal(r#""a\qb""#).unwrap_err();
+        assert_eq!(err.location.offset, 3);
+        assert!(err.expected.tokens().any(|t| t == "escape sequence"));
+        assert!(fn_program::string_literal(r#""\u{110000}""#).is_err());
+    }
+
+    #[test]
+    fn parse_digit_separators() {
+        assert_eq!(int_value("1_000_000"), 1000000);
+        assert_eq!(int_value("0xFF_FF"), 0xFFFF);
+        assert_eq!(float_value("3.141_592"), float_value("3.141592"));
+        assert_eq!(float_value("1_0.5e1_0"), 10.5e10);
+        for code in ["1__0", "_1", "1_", "1._5", "1.5_"] {
+            assert!(fn_program::int_literal(code).is_err(), "{code} as int");
+            assert!(fn_program::float_literal(code).is_err(), "{code} as float");
+        }
+    }
+
+    fn float_value(code: &str) -> f64 {
+        match fn_program::float_literal(code).unwrap() {
+            Literal::Float(v, _, _) => v,
+            _ => panic!("expected a float literal from {code}"),
+        }
+    }
+
+    #[test]
+    fn parse_float_literal() {
+        assert_eq!(float_value("0.0"), 0.0);
+        assert_eq!(float_value("1.25"), 1.25);
+        assert_eq!(float_value("1e10"), 1e10);
+        assert_eq!(float_value("2.5E-3"), 2.5e-3);
+        assert_eq!(float_value("6.022e23"), 6.022e23);
+        assert!(fn_program::float_literal(".5").is_err());
+        assert!(fn_program::float_literal("3.14foo").is_err());
+    }
+
+    #[test]
+    fn parse_literal_among_ids() {
+        let exprs = fn_program::start("[a 42 b]").unwrap();
+        assert_eq!(exprs.len(), 3);
+        assert!(matches!(&exprs[0], Expr::Id(s) if s.value == "a"));
+        assert!(matches!(&exprs[1], Expr::Literal(Literal::Int(42, _, _))));
+        assert!(matches!(&exprs[2], Expr::Id(s) if s.value == "b"));
+    }
+
+    #[test]
+    fn parse_backend_symbol() {
+        let sym = fn_program::symbol("@q0").unwrap();
+        assert_eq!(sym.value, "q0");
+        assert!(matches!(sym.backend_kind, BackendKind::QPU));
+        assert_eq!(sym.to_string(), "@q0");
+
+        let sym = fn_program::symbol("x").unwrap();
+        assert!(matches!(sym.backend_kind, BackendKind::CPU));
+        assert_eq!(sym.to_string(), "x");
+    }
+
+    #[test]
+    fn parse_no_arg_modifiers() {
+        let m = fn_program::modifier("<mut>").unwrap();
+        assert_eq!(m.name.value, "mut");
+        assert!(m.value.is_none());
+
+        let m = fn_program::modifier("<&>").unwrap();
+        assert_eq!(m.name.value, "&");
+        assert!(m.value.is_none());
+
+        let ms = fn_program::modifiers("<mut><&>").unwrap();
+        let names: Vec<&str> = ms.iter().map(|m| m.name.value.as_str()).collect();
+        assert_eq!(names, ["mut", "&"]);
+
+        assert!(fn_program::modifiers("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_single_arg_modifiers() {
+        let m = fn_program::modifier("<shots=1000>").unwrap();
+        assert_eq!(m.name.value, "shots");
+        assert!(matches!(m.value, Some(Expr::Literal(Literal::Int(1000, _, _)))));
+
+        let m = fn_program::modifier("<device=qiskit.aer-sim>").unwrap();
+        assert_eq!(m.name.value, "device");
+        match m.value {
+            Some(Expr::DataMemberAccess(cs)) => assert_eq!(cs.to_string(), "qiskit.aer-sim"),
+            _ => panic!("expected member access value for device"),
+        }
+
+        assert!(fn_program::modifier("<shots=>").is_err());
+    }
+
+    fn member_values(code: &str) -> Vec<String> {
+        match fn_program::member_access(code).unwrap() {
+            Expr::DataMemberAccess(cs) => cs.value.into_iter().map(|s| s.value).collect(),
+            _ => panic!("expected member access from {code}"),
+        }
+    }
+
+    #[test]
+    fn parse_member_access() {
+        assert_eq!(member_values("a.b.c"), ["a", "b", "c"]);
+        assert_eq!(member_values("var.{x y}"), ["var", "x", "y"]);
+        assert!(matches!(fn_program::member_access("a"), Ok(Expr::Id(s)) if s.value == "a"));
+        assert!(fn_program::member_access("a.").is_err());
+    }
+
+    #[test]
+    fn parse_comments() {
+        let exprs = fn_program::start("[a # note\n b]").unwrap();
+        let names: Vec<&str> = exprs.iter()
+            .map(|e| match e {
+                Expr::Id(s) => s.value.as_str(),
+                _ => panic!("expected identifiers only"),
+            })
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+
+        assert_eq!(fn_program::start("[a #{ outer #{ inner }# \n still }# b]").unwrap().len(), 2);
+        assert!(fn_program::start("[a #{ unclosed #{ inner }# b]").is_err());
+        assert!(type_program::whitespace("# types\n#{ block }#").is_ok());
+        assert!(const_program::whitespace(" ; # consts").is_err());
+    }
+
+    #[test]
+    fn parse_struct_def() {
+        let def = type_program::struct_def("struct point { x:i32 y:i32 }").unwrap();
+        assert_eq!(def.name.value, "point");
+        let members: Vec<(&str, &str)> = def.members.iter()
+            .map(|m| (m.name.value.as_str(), m.ty.name.value.as_str()))
+            .collect();
+        assert_eq!(members, [("x", "i32"), ("y", "i32")]);
+        assert!(type_program::struct_def("structpoint { x:i32 }").is_err());
+
+        let def = type_program::struct_def("struct pair {\n  a: u8,\n  b: f64,\n}").unwrap();
+        assert_eq!(def.members.len(), 2);
+    }
+
+    #[test]
+    fn parse_enum_def() {
+        let Ok(TypeDef::EnumDef { name, members, .. }) = type_program::enum_def("enum status { ON OFF }") else {
+            panic!("expected enum definition");
+        };
+        assert_eq!(name.value, "status");
+        assert_eq!(members.len(), 2);
+        assert!(matches!(&members[0], EnumMember::KindMember(s) if s.value == "ON"));
+        assert!(matches!(&members[1], EnumMember::KindMember(s) if s.value == "OFF"));
+        assert!(type_program::enum_def("enumstatus { ON OFF }").is_err());
+
+        let code = "enum color { rgb{r:u8 g:u8 b:u8} hex{value:u32} }";
+        let Ok(TypeDef::EnumDef { members, .. }) = type_program::enum_def(code) else {
+            panic!("expected enum definition");
+        };
+        assert_eq!(members.len(), 2);
+        assert!(matches!(&members[0], EnumMember::StructMember(d) if d.name.value == "rgb" && d.members.len() == 3));
+        assert!(matches!(&members[1], EnumMember::StructMember(d) if d.name.value == "hex" && d.members.len() == 1));
+    }
+
+    #[test]
+    fn parse_consts() {
+        let code = "const PI: f64\n<&> const E: f32\n";
+        let Ok(Content::Consts(consts)) = const_program::consts(code) else {
+            panic!("expected constants content");
+        };
+        let defs: Vec<(&str, &str)> = consts.iter()
+            .map(|c| (c.name.value.as_str(), c.ty.name.value.as_str()))
+            .collect();
+        assert_eq!(defs, [("PI", "f64"), ("E", "f32")]);
+        assert!(consts[0].modifiers.is_empty());
+        assert_eq!(consts[1].modifiers[0].name.value, "&");
+        assert!(const_program::const_def("constPI: f64").is_err());
+    }
+
+    #[test]
+    fn parse_fn_def() {
+        let def = fn_program::fn_def("fn add(a:i32 b:i32) i32 { return a }").unwrap();
+        assert_eq!(def.name.value, "add");
+        assert!(fn_program::fn_def("fnadd(a:i32 b:i32) i32 { return a }").is_err());
+        let params: Vec<(&str, &str)> = def.params.iter()
+            .map(|p| (p.name.value.as_str(), p.ty.name.value.as_str()))
+            .collect();
+        assert_eq!(params, [("a", "i32"), ("b", "i32")]);
+        assert_eq!(def.ty.name.value, "i32");
+        assert!(def.modifiers.is_empty());
+        assert_eq!(def.body.as_slice().len(), 1);
+
+        let def = fn_program::fn_def("<mut> fn noop() u32 {}").unwrap();
+        assert!(def.params.is_empty());
+        assert_eq!(def.modifiers[0].name.value, "mut");
+    }
+
+    #[test]
+    fn parse_struct_assign() {
+        let Ok(Stmt::Assign(Assign::Struct { name, ty, members })) = fn_program::stmt("p = point{ x:1 y:2 }") else {
+            panic!("expected struct assignment");
+        };
+        assert_eq!(name.value, "p");
+        assert_eq!(ty.unwrap().value, "point");
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].name.value, "x");
+        assert!(matches!(members[1].value, Expr::Literal(Literal::Int(2, _, _))));
+
+        let stmt = fn_program::stmt("p = point{ x:1 y:2 }").unwrap();
+        assert_eq!(stmt.to_string(), "p = point{x:1 y:2}");
+        assert!(matches!(fn_program::stmt("p = { }"), Ok(Stmt::Assign(Assign::Struct { ty: None, .. }))));
+    }
+
+    #[test]
+    fn parse_params() {
+        let params = fn_program::params("(a:i32 <mut> b:f64)").unwrap();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].name.value, "a");
+        assert!(params[0].modifiers.is_empty());
+        assert_eq!(params[1].name.value, "b");
+        assert_eq!(params[1].ty.name.value, "f64");
+        assert_eq!(params[1].modifiers[0].name.value, "mut");
+        assert!(fn_program::params("( )").unwrap().is_empty());
+
+        let def = fn_program::fn_def("fn scale(<mut> v:f64 k:f64) null {}").unwrap();
+        assert_eq!(def.params[0].modifiers.len(), 1);
+    }
+
+    #[test]
+    fn parse_metafn_def() {
+        let def = fn_program::metafn_def("<&> metafn twice(f:fn_t) { f() }").unwrap();
+        assert_eq!(def.name.value, "twice");
+        assert_eq!(def.params.len(), 1);
+        assert_eq!(def.params[0].ty.name.value, "fn_t");
+        assert_eq!(def.modifiers[0].name.value, "&");
+        assert!(matches!(def.body.as_slice(), [Stmt::Expr(Expr::Call { .. })]));
+
+        let (_, content) = fn_program::module("fn id(x:i32) i32 { return x }\nmetafn twice(f:fn_t) { f() }").unwrap();
+        assert!(matches!(content, Content::Groups(g) if matches!(g[..], [GroupsDef::FnDef(_), GroupsDef::MetaFnDef(_)])));
+        assert!(fn_program::metafn_def("metafntwice(f:fn_t) {}").is_err());
+    }
+
+    #[test]
+    fn parse_modifier_def() {
+        let def = fn_program::modifier_def("modifier flag() {}").unwrap();
+        assert_eq!(def.name.value, "flag");
+        assert!(matches!(def.params, [None, None]));
+
+        let def = fn_program::modifier_def("modifier shots(n:u32) { n }").unwrap();
+        assert!(matches!(&def.params, [Some(p), None] if p.name.value == "n"));
+        assert_eq!(def.body.len(), 1);
+
+        let def = fn_program::modifier_def("<&> modifier range(a:u32 b:u32) {}").unwrap();
+        assert!(matches!(&def.params, [Some(a), Some(b)] if a.name.value == "a" && b.name.value == "b"));
+        assert_eq!(def.modifiers[0].name.value, "&");
+
+        assert!(fn_program::modifier_def("modifier many(a:u32 b:u32 c:u32) {}").is_err());
+        let (_, content) = fn_program::module("modifier flag() {}").unwrap();
+        assert!(matches!(content, Content::Groups(g) if matches!(g[..], [GroupsDef::ModifierDef(_)])));
+    }
+
+    #[test]
+    fn parse_type_name() {
+        let ty = fn_program::type_name("i32").unwrap();
+        assert_eq!(ty.name.value, "i32");
+        assert!(ty.modifiers.is_empty());
+
+        let ty = fn_program::type_name("<mut> i32").unwrap();
+        assert_eq!(ty.name.value, "i32");
+        assert_eq!(ty.modifiers[0].name.value, "mut");
+
+        let ty = fn_program::type_name("@qubit").unwrap();
+        assert_eq!(ty.name.value, "qubit");
+        assert_eq!(ty.name.backend_kind, BackendKind::QPU);
+
+        let member = type_program::struct_def("struct cell { v:<mut> i32 }").unwrap().members.remove(0);
+        assert_eq!(member.ty.modifiers.len(), 1);
+        let Ok(Stmt::Declare { ty, .. }) = const_program::stmt("q: @qubit") else {
+            panic!("expected declaration");
+        };
+        assert_eq!(ty.name.backend_kind, BackendKind::QPU);
+    }
+
+    #[test]
+    fn parse_backend_import() {
+        let import = fn_program::import_path("quantum.gates.@h").unwrap();
+        assert_eq!(import.path, Path::from_dotted("quantum.gates"));
+        assert_eq!(import.name, Symbol::new(String::from("h"), BackendKind::QPU));
+        assert_eq!(import.to_string(), "quantum.gates.@h");
+
+        let Ok(Imports::Fns(paths)) = fn_program::import("use fns quantum.gates.@h math.add") else {
+            panic!("expected fns import");
+        };
+        assert_eq!(paths[1].name.backend_kind, BackendKind::CPU);
+        assert!(fn_program::import_path("quantum.@gates.h").is_err());
+    }
+
+    #[test]
+    fn parse_assign() {
+        let Ok(Stmt::Assign(Assign::Single { name, value, modifiers })) = fn_program::stmt("x = add(1 2)") else {
+            panic!("expected assignment");
+        };
+        assert_eq!(name.value, "x");
+        assert!(matches!(value, Expr::Call { callee, .. } if callee.value == "add"));
+        assert!(modifiers.is_empty());
+
+        assert!(fn_program::assign_stmt("x: i32 = 1").is_err());
+        assert!(matches!(fn_program::stmt("x: i32 = 1"), Ok(Stmt::DeclareAssign { .. })));
+        let stmt = fn_program::stmt("<mut> x = 1").unwrap();
+        assert_eq!(stmt.to_string(), "<mut> x = 1");
+
+        let block = fn_program::block("{ x: i32 = 1 x = add(x 1) return x }").unwrap();
+        assert!(matches!(block.as_slice()[1], Stmt::Assign(Assign::Single { .. })));
+    }
+
+    #[test]
+    fn parse_optbdn_call() {
+        let code = "switch(x){ 1:{ return a } 2:{ return b } }";
+        let Ok(Expr::MetaCall(MetaCall::OptBdn { name, args, body, .. })) = fn_program::expr(code) else {
+            panic!("expected option-body meta-call");
+        };
+        assert_eq!(name.value, "switch");
+        assert_eq!(args.len(), 1);
+        assert_eq!(body.len(), 2);
+        assert!(matches!(body[0].opt, Expr::Literal(Literal::Int(1, _, _))));
+        assert!(matches!(body[1].body.as_slice(), [Stmt::Return(Expr::Id(s))] if s.value == "b"));
+
+        let expr = fn_program::expr(code).unwrap();
+        assert_eq!(fn_program::expr(&expr.to_string()).unwrap(), expr);
+        assert!(matches!(
+            fn_program::expr("loop(i){ x: i32 = i }"),
+            Ok(Expr::MetaCall(MetaCall::Bdn { .. }))
+        ));
+    }
+
+    #[test]
+    fn parse_module_by_kind() {
+        assert!(matches!(parse_module("const PI: f64"), Ok(Content::Consts(c)) if c.len() == 1));
+        assert!(matches!(parse_module("struct point { x:i32 }"), Ok(Content::Types(_))));
+        assert!(matches!(parse_module("enum status { ON OFF }"), Ok(Content::Types(_))));
+        assert!(matches!(parse_module("use fns a.b\nfn main() null {}"), Ok(Content::Groups(_))));
+        assert!(matches!(parse_module("metafn twice(f:fn_t) {}"), Ok(Content::Groups(_))));
+
+        assert!(matches!(parse_module(" \n"), Err(ModuleError::EmptyContent)));
+        assert!(matches!(parse_module("const PI: f64\nfn main() null {}"), Err(ModuleError::MixedContent)));
+        assert!(matches!(parse_module("fn main( null {}"), Err(ModuleError::ParseFailed { .. })));
+    }
+
+    #[test]
+    fn literal_spans() {
+        let exprs = fn_program::start("[42]").unwrap();
+        let Expr::Literal(lit) = &exprs[0] else {
+            panic!("expected literal");
+        };
+        assert_eq!(lit.span(), Some((1, 3)));
+        let Ok(Expr::Call { args, .. }) = fn_program::expr("f(1.5 @\"q\")") else {
+            panic!("expected call");
+        };
+        let spans: Vec<_> = args.iter()
+            .map(|arg| match arg {
+                Expr::Literal(lit) => lit.span(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(spans, vec![Some((2, 5)), Some((6, 10))]);
+    }
+
+    #[test]
+    fn parse_block() {
+        let block = fn_program::block("{ return a; b }").unwrap();
+        let stmts = block.as_slice();
+        assert_eq!(stmts.len(), 2);
+        assert!(matches!(&stmts[0], Stmt::Return(Expr::Id(s)) if s.value == "a"));
+        assert!(matches!(&stmts[1], Stmt::Expr(Expr::Id(s)) if s.value == "b"));
+
+        assert!(fn_program::block("{}").unwrap().as_slice().is_empty());
+        assert!(fn_program::block("{ ;\n }").unwrap().as_slice().is_empty());
+    }
+
+    #[test]
+    fn parse_stmt_separators() {
+        let count = |code| fn_program::block(code).unwrap().len();
+        assert_eq!(count("{ a b }"), 2);
+        assert_eq!(count("{\n  x: i64 = 1\n  print(x)\n}"), 2);
+        assert_eq!(count("{ x: i64 = 1; print(x); return x; }"), 3);
+        assert_eq!(count("{ a # note\n b }"), 2);
+        assert!(fn_program::block("{ a, b }").is_err());
+        assert!(fn_program::block("{ return; x }").is_err());
+        assert!(fn_program::block("{ x = ; 1 }").is_err());
+        assert!(fn_program::block("{ x: i32 ; = 1 }").is_err());
+        assert_eq!(type_program::struct_def("struct point { x:i32, y:i32 }").unwrap().members.len(), 2);
+
+        let Ok(Expr::Call { args, .. }) = fn_program::expr("f(a,b)") else {
+            panic!("expected a call");
+        };
+        assert_eq!(args.len(), 2);
+    }
+
+    #[test]
+    fn parse_return_stmt() {
+        let stmt = fn_program::return_stmt("return x").unwrap();
+        assert!(matches!(stmt, Stmt::Return(Expr::Id(s)) if s.value == "x"));
+
+        assert!(fn_program::return_stmt("returnX").is_err());
+        let stmt = fn_program::stmt("returnX").unwrap();
+        assert!(matches!(stmt, Stmt::Expr(Expr::Id(s)) if s.value == "returnX"));
+    }
+
+    #[test]
+    fn keywords_are_reserved() {
+        assert!(fn_program::vals("return").is_err());
+        assert_eq!(fn_program::vals("returnX").unwrap(), "returnX");
+        assert!(fn_program::vals("fn-name").is_ok());
+        assert!(fn_program::stmt("return").is_err());
+        assert!(fn_program::declare_stmt("const: i64").is_err());
+
+        let def = fn_program::fn_def("fn cast (data:u32 to:u64) u64 { return data }").unwrap();
+        assert_eq!(def.name.value, "cast");
+        assert!(fn_program::expr("cast(x)").is_err());
+    }
+
+    #[test]
+    fn parse_declare_stmt() {
+        let Ok(Stmt::DeclareAssign { name, ty, modifiers, value }) = fn_program::stmt("x: i32 = 42") else {
+            panic!("expected declare-assign statement");
+        };
+        assert_eq!(name.value, "x");
+        assert_eq!(ty.name.value, "i32");
+        assert!(modifiers.is_empty());
+        assert!(matches!(value, Expr::Literal(Literal::Int(42, _, _))));
+
+        let Ok(Stmt::Declare { name, ty, .. }) = fn_program::stmt("x: i32") else {
+            panic!("expected declare statement");
+        };
+        assert_eq!(name.value, "x");
+        assert_eq!(ty.name.value, "i32");
+
+        let block = fn_program::block("{ <mut> x:i32 y:i32 = 1 x }").unwrap();
+        let stmts = block.as_slice();
+        assert!(matches!(&stmts[0], Stmt::Declare { modifiers, .. } if modifiers.len() == 1));
+        assert!(matches!(&stmts[1], Stmt::DeclareAssign { .. }));
+        assert!(matches!(&stmts[2], Stmt::Expr(Expr::Id(_))));
+    }
+
+    #[test]
+    fn parse_call() {
+        let Ok(Expr::Call { callee, args, modifiers }) = fn_program::call("f()") else {
+            panic!("expected call");
+        };
+        assert_eq!(callee.value, "f");
+        assert!(args.is_empty() && modifiers.is_empty());
+
+        let Ok(Expr::Call { callee, args, .. }) = fn_program::call("add(1 2)") else {
+            panic!("expected call");
+        };
+        assert_eq!(callee.value, "add");
+        assert!(matches!(args[..], [Expr::Literal(Literal::Int(1, _, _)), Expr::Literal(Literal::Int(2, _, _))]));
+
+        let Ok(Expr::Call { callee, args, modifiers }) = fn_program::call("@run(@q)<shots=1000>") else {
+            panic!("expected call");
+        };
+        assert!(matches!(callee.backend_kind, BackendKind::QPU));
+        assert!(matches!(&args[0], Expr::Id(s) if s.to_string() == "@q"));
+        assert_eq!(modifiers[0].name.value, "shots");
+        assert!(matches!(modifiers[0].value, Some(Expr::Literal(Literal::Int(1000, _, _)))));
+
+        let Ok(Expr::Call { args, .. }) = fn_program::expr("print(add(a b))") else {
+            panic!("expected call");
+        };
+        assert!(matches!(&args[0], Expr::Call { callee, .. } if callee.value == "add"));
+    }
+
+    #[test]
+    fn parse_optn_call() {
+        let code = "match(x:{ return 1 } y:{ return 2 })";
+        let Ok(Expr::MetaCall(MetaCall::Optn { name, options, modifiers })) = fn_program::expr(code) else {
+            panic!("expected option meta-call");
+        };
+        assert_eq!(name.value, "match");
+        assert!(modifiers.is_empty());
+        assert_eq!(options.len(), 2);
+        assert!(matches!(&options[0].opt, Expr::Id(s) if s.value == "x"));
+        assert!(matches!(options[0].body.as_slice(), [Stmt::Return(Expr::Literal(Literal::Int(1, _, _)))]));
+        assert!(matches!(&options[1].opt, Expr::Id(s) if s.value == "y"));
+        assert!(matches!(options[1].body.as_slice(), [Stmt::Return(Expr::Literal(Literal::Int(2, _, _)))]));
+    }
+
+    #[test]
+    fn parse_bdn_call() {
+        let Ok(Expr::MetaCall(MetaCall::Bdn { name, args, body, .. })) = fn_program::expr("loop(i){ return i }") else {
+            panic!("expected body meta-call");
+        };
+        assert_eq!(name.value, "loop");
+        assert!(matches!(&args[..], [Expr::Id(s)] if s.value == "i"));
+        assert!(matches!(body.as_slice(), [Stmt::Return(Expr::Id(s))] if s.value == "i"));
+
+        let Ok(Expr::MetaCall(MetaCall::Bdn { args, body, .. })) = fn_program::expr("pipe(var) { double print }") else {
+            panic!("expected body meta-call");
+        };
+        assert_eq!(args.len(), 1);
+        assert_eq!(body.as_slice().len(), 2);
+
+        assert!(matches!(fn_program::expr("loop(i)"), Ok(Expr::Call { .. })));
+    }
+
+    #[test]
+    fn parse_cast() {
+        let Ok(Expr::Cast { value, to_ty, modifiers }) = fn_program::expr("42 * f64") else {
+            panic!("expected cast");
+        };
+        assert!(matches!(*value, Expr::Literal(Literal::Int(42, _, _))));
+        assert_eq!(to_ty.name.value, "f64");
+        assert!(modifiers.is_empty());
+
+        let Ok(Expr::Cast { value, to_ty, modifiers }) = fn_program::expr("add(a b) * u64<&>") else {
+            panic!("expected cast");
+        };
+        assert!(matches!(*value, Expr::Call { .. }));
+        assert_eq!(to_ty.name.value, "u64");
+        assert_eq!(modifiers[0].name.value, "&");
+
+        let Ok(Expr::Cast { value, to_ty, .. }) = fn_program::expr("@q * hashmap * str") else {
+            panic!("expected cast");
+        };
+        assert_eq!(to_ty.name.value, "str");
+        assert!(matches!(*value, Expr::Cast { .. }));
+    }
+
+    #[test]
+    fn parse_symbol_span() {
+        let exprs = fn_program::start("[a bb]").unwrap();
+        assert!(matches!(&exprs[1], Expr::Id(s) if s.span == Some((3, 5))));
+
+        let sym = fn_program::symbol("@q0").unwrap();
+        assert_eq!(sym.span, Some((0, 3)));
+    }
+
+    #[test]
+    fn parse_imports() {
+        let imports = fn_program::imports("use types a.b.T\nuse fns a.b.cfn d.efn").unwrap();
+        assert_eq!(imports.len(), 2);
+        let Imports::Types(types) = &imports[0] else {
+            panic!("expected types import");
+        };
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].name.value, "T");
+        assert_eq!(types[0].path.to_string(), "a.b");
+        let Imports::Fns(fns) = &imports[1] else {
+            panic!("expected fns import");
+        };
+        let names: Vec<String> = fns.iter().map(|s| s.to_string()).collect();
+        assert_eq!(names, ["a.b.cfn", "d.efn"]);
+
+        let imports = fn_program::imports("use fns a.f\nuse types a.T\nuse fns b.g").unwrap();
+        assert!(matches!(&imports[..], [Imports::Fns(fns), Imports::Types(_)] if fns.len() == 2));
 
+        assert!(fn_program::import("use things a.b").is_err());
     }
-);
+}
@@ -0,0 +1,113 @@
+//! Constant folding pass.
+//!
+//! Calls to the arithmetic built-ins whose arguments are all literals are
+//! evaluated at compile time, using the interpreter's semantics.
+//!
+
+use crate::ir::hir::{Expr, Literal, Symbol};
+use crate::ir::ids::BackendKind;
+use crate::runtime::interpreter::Interpreter;
+use crate::runtime::value::Value;
+
+
+/// Fold the literal-only arithmetic calls of `expr`, innermost first.
+///
+/// The folded literal keeps the backend of its arguments, which must all
+/// share the same one. Calls that cannot be folded (mixed backends, errors
+/// such as division by zero, unknown callees) are left as they are, so the
+/// error surfaces wherever the expression is evaluated.
+///
+pub fn fold_constants(expr: Expr) -> Expr {
+    match expr {
+        Expr::Call { callee, args, modifiers } => {
+            let args: Vec<Expr> = args.into_iter().map(fold_constants).collect();
+            match fold_call(&callee, &args) {
+                Some(lit) if modifiers.is_empty() => Expr::Literal(lit),
+                _ => Expr::Call { callee, args, modifiers },
+            }
+        }
+        Expr::Cast { value, to_ty, modifiers } => Expr::Cast {
+            value: Box::new(fold_constants(*value)),
+            to_ty,
+            modifiers,
+        },
+        other => other,
+    }
+}
+
+fn fold_call(callee: &Symbol, args: &[Expr]) -> Option<Literal> {
+    let mut backend = None;
+    let mut values = Vec::with_capacity(args.len());
+    for arg in args {
+        let Expr::Literal(lit) = arg else {
+            return None;
+        };
+        let kind = literal_backend(lit);
+        if backend.is_some_and(|b| b != kind) {
+            return None;
+        }
+        backend = Some(kind);
+        values.push(Interpreter::eval_literal(lit));
+    }
+    let kind = backend?;
+    let value = Interpreter::eval_call(callee, &values).ok()?;
+    Some(match value {
+        Value::Bool(v) => Literal::Bool(v, kind, None),
+        Value::Int(v) => Literal::Int(v, kind, None),
+        Value::Float(v) => Literal::Float(v, kind, None),
+        Value::Str(v) => Literal::Str(v, kind, None),
+    })
+}
+
+fn literal_backend(lit: &Literal) -> BackendKind {
+    match lit {
+        Literal::Bool(_, kind, _)
+        | Literal::Int(_, kind, _)
+        | Literal::Float(_, kind, _)
+        | Literal::Str(_, kind, _) => *kind,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::{Expr, Literal};
+    use crate::ir::ids::BackendKind;
+    use crate::parse::parser::fn_program;
+    use crate::passes::fold::fold_constants;
+
+    fn fold(code: &str) -> Expr {
+        fold_constants(fn_program::expr(code).unwrap())
+    }
+
+    #[test]
+    fn fold_literal_call() {
+        assert_eq!(fold("add(2 3)"), Expr::Literal(Literal::Int(5, BackendKind::CPU, None)));
+        assert_eq!(fold("mul(add(1 1) 1.5)"), Expr::Literal(Literal::Float(3.0, BackendKind::CPU, None)));
+    }
+
+    #[test]
+    fn fold_with_variable() {
+        assert_eq!(fold("add(x 3)"), fn_program::expr("add(x 3)").unwrap());
+        assert_eq!(fold("add(x sub(5 2))"), fn_program::expr("add(x 3)").unwrap());
+    }
+
+    #[test]
+    fn no_fold_across_backends() {
+        let mixed = Expr::Call {
+            callee: fn_program::symbol("add").unwrap(),
+            args: vec![
+                Expr::Literal(Literal::Int(2, BackendKind::CPU, None)),
+                Expr::Literal(Literal::Int(3, BackendKind::QPU, None)),
+            ],
+            modifiers: vec![],
+        };
+        assert_eq!(fold_constants(mixed.clone()), mixed);
+
+        let mut qpu = mixed;
+        let Expr::Call { args, .. } = &mut qpu else { unreachable!() };
+        args[0] = Expr::Literal(Literal::Int(2, BackendKind::QPU, None));
+        assert_eq!(fold_constants(qpu), Expr::Literal(Literal::Int(5, BackendKind::QPU, None)));
+        assert_eq!(fold("div(1 0)"), fn_program::expr("div(1 0)").unwrap());
+    }
+}
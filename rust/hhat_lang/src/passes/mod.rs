@@ -0,0 +1,4 @@
+pub(crate) mod fold;
+pub(crate) mod names;
+pub(crate) mod unused;
+pub(crate) mod validate;
@@ -0,0 +1,256 @@
+//! Name resolution pass.
+//!
+//! Checks that every symbol referenced by expressions in a module is either
+//! defined in the module (constants, types, functions, modifiers and
+//! meta-functions), imported, or bound locally (parameters and declarations).
+//!
+
+use crate::ir::hir::{
+    Assign, Block, Content, Expr, GroupsDef, Imports, MetaCall, Param, Stmt, Symbol, TypeDef,
+};
+use crate::ir::ids::{Interner, SymbolContext};
+use crate::utils::errors::ModuleError;
+
+
+/// Resolve the names used in a module's content against its own definitions,
+/// its imports and the symbols already in `ctx` (built-ins, for instance),
+/// returning an error for each undefined symbol.
+///
+/// The module's bindings live in scopes pushed on top of `ctx`, which is left
+/// as it was given once resolution is done.
+pub fn resolve_names(
+    imports: &[Imports],
+    content: &Content,
+    ctx: &mut SymbolContext,
+) -> Vec<ModuleError> {
+    ctx.push_scope();
+    let mut resolver = Resolver { ctx, interner: Interner::new(), errors: vec![] };
+    resolver.declare_imports(imports);
+    resolver.declare_content(content);
+    resolver.resolve_content(content);
+    let errors = resolver.errors;
+    ctx.pop_scope();
+    errors
+}
+
+
+struct Resolver<'a> {
+    ctx: &'a mut SymbolContext,
+    interner: Interner,
+    errors: Vec<ModuleError>,
+}
+
+impl Resolver<'_> {
+    fn declare(&mut self, sym: &Symbol) {
+        let name = sym.to_string();
+        let id = self.interner.intern(&name);
+        self.ctx.insert(&name, id);
+    }
+
+    fn declare_imports(&mut self, imports: &[Imports]) {
+        for import in imports {
+            let paths = match import {
+                Imports::Consts(p)
+                | Imports::Types(p)
+                | Imports::Fns(p)
+                | Imports::Modifiers(p)
+                | Imports::MetaFns(p) => p,
+            };
+            for path in paths {
+                self.declare(&path.name);
+            }
+        }
+    }
+
+    fn declare_content(&mut self, content: &Content) {
+        match content {
+            Content::Consts(consts) => {
+                for c in consts {
+                    self.declare(&c.name);
+                }
+            }
+            Content::Types(types) => {
+                for ty in types {
+                    match ty {
+                        TypeDef::PrimitiveDef(_) => {}
+                        TypeDef::StructDef(def) => self.declare(&def.name),
+                        TypeDef::EnumDef { name, .. } | TypeDef::NamedType { name } => {
+                            self.declare(name)
+                        }
+                    }
+                }
+            }
+            Content::Groups(groups) => {
+                for group in groups {
+                    match group {
+                        GroupsDef::FnDef(def) => self.declare(&def.name),
+                        GroupsDef::ModifierDef(def) => self.declare(&def.name),
+                        GroupsDef::MetaFnDef(def) => self.declare(&def.name),
+                    }
+                }
+            }
+        }
+    }
+
+    fn resolve_content(&mut self, content: &Content) {
+        let Content::Groups(groups) = content else {
+            return;
+        };
+        for group in groups {
+            match group {
+                GroupsDef::FnDef(def) => self.resolve_body(def.params.iter(), &def.body),
+                GroupsDef::ModifierDef(def) => {
+                    self.resolve_body(def.params.iter().flatten(), &def.body)
+                }
+                GroupsDef::MetaFnDef(def) => self.resolve_body(def.params.iter(), &def.body),
+            }
+        }
+    }
+
+    fn resolve_body<'a>(&mut self, params: impl Iterator<Item = &'a Param>, body: &Block) {
+        self.ctx.push_scope();
+        for param in params {
+            self.declare(&param.name);
+        }
+        self.resolve_block(body);
+        self.ctx.pop_scope();
+    }
+
+    fn resolve_block(&mut self, block: &Block) {
+        self.ctx.push_scope();
+        for stmt in block {
+            self.resolve_stmt(stmt);
+        }
+        self.ctx.pop_scope();
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Declare { name, .. } => self.declare(name),
+            Stmt::DeclareAssign { name, value, .. } => {
+                self.resolve_expr(value);
+                self.declare(name);
+            }
+            Stmt::Assign(Assign::Single { name, value, .. }) => {
+                self.resolve_symbol(name);
+                self.resolve_expr(value);
+            }
+            Stmt::Assign(Assign::Struct { name, members, .. }) => {
+                self.resolve_symbol(name);
+                for member in members {
+                    self.resolve_expr(&member.value);
+                }
+            }
+            Stmt::Assign(Assign::Enum { .. }) => {}
+            Stmt::Expr(expr) | Stmt::Return(expr) => self.resolve_expr(expr),
+        }
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Id(sym) => self.resolve_symbol(sym),
+            Expr::Literal(_) => {}
+            Expr::Call { callee, args, .. } => {
+                self.resolve_symbol(callee);
+                for arg in args {
+                    self.resolve_expr(arg);
+                }
+            }
+            Expr::MetaCall(call) => match call {
+                MetaCall::Optn { name, options, .. } => {
+                    self.resolve_symbol(name);
+                    for option in options {
+                        self.resolve_expr(&option.opt);
+                        self.resolve_block(&option.body);
+                    }
+                }
+                MetaCall::Bdn { name, args, body, .. } => {
+                    self.resolve_symbol(name);
+                    for arg in args {
+                        self.resolve_expr(arg);
+                    }
+                    self.resolve_block(body);
+                }
+                MetaCall::OptBdn { name, args, body, .. } => {
+                    self.resolve_symbol(name);
+                    for arg in args {
+                        self.resolve_expr(arg);
+                    }
+                    for option in body {
+                        self.resolve_expr(&option.opt);
+                        self.resolve_block(&option.body);
+                    }
+                }
+            },
+            Expr::Cast { value, .. } => self.resolve_expr(value),
+            // only the base of a member access is a name in scope
+            Expr::DataMemberAccess(composite) => {
+                if let Some(base) = composite.value.first() {
+                    self.resolve_symbol(base);
+                }
+            }
+        }
+    }
+
+    /// Qubit references (`@q0`) are always in scope.
+    fn resolve_symbol(&mut self, sym: &Symbol) {
+        if sym.qubit_index().is_none() && self.ctx.lookup(&sym.to_string()).is_none() {
+            self.errors.push(ModuleError::UndefinedSymbol {
+                name: sym.to_string(),
+                span: sym.span,
+            });
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::{Content, GroupsDef};
+    use crate::ir::ids::{SymbolContext, SymbolId};
+    use crate::parse::parser::fn_program;
+    use crate::passes::names::resolve_names;
+    use crate::utils::errors::ModuleError;
+
+    fn groups(fns: &[&str]) -> Content {
+        Content::Groups(
+            fns.iter()
+                .map(|code| GroupsDef::FnDef(fn_program::fn_def(code).unwrap()))
+                .collect()
+        )
+    }
+
+    #[test]
+    fn resolved_program() {
+        let imports = fn_program::imports("use fns math.add").unwrap();
+        let content = groups(&[
+            "fn id(a:i32) i32 { return a }",
+            "fn two() i32 { x:i32 = id(2) return add(x x) }",
+        ]);
+        assert!(resolve_names(&imports, &content, &mut SymbolContext::new()).is_empty());
+    }
+
+    #[test]
+    fn resolved_with_context() {
+        let content = groups(&["fn f(a:i32) i32 { return print(a) }"]);
+        let mut ctx = SymbolContext::new();
+        assert_eq!(resolve_names(&[], &content, &mut ctx).len(), 1);
+        ctx.insert("print", SymbolId(0));
+        assert!(resolve_names(&[], &content, &mut ctx).is_empty());
+        assert_eq!(ctx.lookup("f"), None);
+    }
+
+    #[test]
+    fn undefined_symbol() {
+        let content = groups(&["fn bad() i32 { x:i32 = 1 return add(x y) }"]);
+        let errors = resolve_names(&[], &content, &mut SymbolContext::new());
+        let names: Vec<&str> = errors.iter()
+            .map(|e| match e {
+                ModuleError::UndefinedSymbol { name, .. } => name.as_str(),
+                _ => panic!("expected undefined symbol errors"),
+            })
+            .collect();
+        assert_eq!(names, ["add", "y"]);
+        assert!(matches!(&errors[1], ModuleError::UndefinedSymbol { span: Some((38, 39)), .. }));
+    }
+}
@@ -0,0 +1,208 @@
+//! Unused imports pass.
+//!
+
+use std::collections::HashSet;
+use crate::ir::hir::{
+    CompositeSymbol, Content, EnumMember, Expr, GroupsDef, Imports, MetaCall, Modifier,
+    Param, Stmt, Symbol, TypeDef, TypeName,
+};
+use crate::ir::visit::{walk_expr, walk_meta_call, walk_stmt, ExprVisitor};
+use crate::utils::errors::Diagnostic;
+
+
+/// Warn about each import whose name is never referenced in `content`,
+/// whether in expressions (calls, values, meta-calls), type positions
+/// (parameters, declarations, casts, members) or modifiers.
+///
+pub fn check_unused_imports(imports: &[Imports], content: &Content) -> Vec<Diagnostic> {
+    let mut usage = Usage::default();
+    usage.visit_content(content);
+    imports.iter()
+        .flat_map(|import| match import {
+            Imports::Consts(p)
+            | Imports::Types(p)
+            | Imports::Fns(p)
+            | Imports::Modifiers(p)
+            | Imports::MetaFns(p) => p,
+        })
+        .filter(|path| !usage.names.contains(&path.name.to_string()))
+        .map(|path| Diagnostic::warning(format!("Unused import `{}`", path), path.name.span))
+        .collect()
+}
+
+
+/// Names referenced by a module, keyed as `sym.to_string()`.
+#[derive(Default)]
+struct Usage {
+    names: HashSet<String>,
+}
+
+impl Usage {
+    fn add(&mut self, sym: &Symbol) {
+        self.names.insert(sym.to_string());
+    }
+
+    fn add_type(&mut self, ty: &TypeName) {
+        self.add(&ty.name);
+        self.add_modifiers(&ty.modifiers);
+    }
+
+    fn add_modifiers(&mut self, modifiers: &[Modifier]) {
+        for modifier in modifiers {
+            self.add(&modifier.name);
+            if let Some(value) = &modifier.value {
+                self.visit_expr(value);
+            }
+        }
+    }
+
+    fn add_params<'a>(&mut self, params: impl IntoIterator<Item = &'a Param>) {
+        for param in params {
+            self.add_type(&param.ty);
+            self.add_modifiers(&param.modifiers);
+        }
+    }
+
+    fn visit_content(&mut self, content: &Content) {
+        match content {
+            Content::Consts(consts) => {
+                for def in consts {
+                    self.add_type(&def.ty);
+                    self.add_modifiers(&def.modifiers);
+                }
+            }
+            Content::Types(types) => {
+                for def in types {
+                    self.visit_type_def(def);
+                }
+            }
+            Content::Groups(groups) => {
+                for group in groups {
+                    self.visit_group(group);
+                }
+            }
+        }
+    }
+
+    fn visit_type_def(&mut self, def: &TypeDef) {
+        let mut structs = vec![];
+        match def {
+            TypeDef::StructDef(def) => structs.push(def),
+            TypeDef::EnumDef { members, modifiers, .. } => {
+                self.add_modifiers(modifiers);
+                structs.extend(members.iter().filter_map(|m| match m {
+                    EnumMember::StructMember(def) => Some(def),
+                    EnumMember::KindMember(_) => None,
+                }));
+            }
+            TypeDef::PrimitiveDef(_) | TypeDef::NamedType { .. } => {}
+        }
+        for def in structs {
+            self.add_modifiers(&def.modifiers);
+            for member in &def.members {
+                self.add_type(&member.ty);
+            }
+        }
+    }
+
+    fn visit_group(&mut self, group: &GroupsDef) {
+        match group {
+            GroupsDef::FnDef(def) => {
+                self.add_params(&def.params);
+                self.add_type(&def.ty);
+                self.add_modifiers(&def.modifiers);
+                self.visit_block(&def.body);
+            }
+            GroupsDef::ModifierDef(def) => {
+                self.add_params(def.params.iter().flatten());
+                self.add_modifiers(&def.modifiers);
+                self.visit_block(&def.body);
+            }
+            GroupsDef::MetaFnDef(def) => {
+                self.add_params(&def.params);
+                self.add_modifiers(&def.modifiers);
+                self.visit_block(&def.body);
+            }
+        }
+    }
+}
+
+impl ExprVisitor for Usage {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Call { modifiers, .. } | Expr::Cast { modifiers, .. } => self.add_modifiers(modifiers),
+            _ => {}
+        }
+        walk_expr(self, expr)
+    }
+
+    fn visit_id(&mut self, sym: &Symbol) {
+        self.add(sym);
+    }
+
+    fn visit_call(&mut self, callee: &Symbol, args: &[Expr]) {
+        self.add(callee);
+        for arg in args {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_meta_call(&mut self, call: &MetaCall) {
+        let (MetaCall::Optn { name, modifiers, .. }
+        | MetaCall::Bdn { name, modifiers, .. }
+        | MetaCall::OptBdn { name, modifiers, .. }) = call;
+        self.add(name);
+        self.add_modifiers(modifiers);
+        walk_meta_call(self, call)
+    }
+
+    fn visit_cast(&mut self, value: &Expr, to_ty: &TypeName) {
+        self.add_type(to_ty);
+        self.visit_expr(value)
+    }
+
+    fn visit_member_access(&mut self, composite: &CompositeSymbol) {
+        if let Some(first) = composite.value.first() {
+            self.add(first);
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Declare { ty, modifiers, .. } | Stmt::DeclareAssign { ty, modifiers, .. } => {
+                self.add_type(ty);
+                self.add_modifiers(modifiers);
+            }
+            _ => {}
+        }
+        walk_stmt(self, stmt)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::parser::fn_program;
+    use crate::passes::unused::check_unused_imports;
+    use crate::utils::errors::Severity;
+
+    #[test]
+    fn one_unused_import() {
+        let code = "use fns math.add math.sub\nfn main() null { return add(1 2) }";
+        let (imports, content) = fn_program::module(code).unwrap();
+        let warnings = check_unused_imports(&imports, &content);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Severity::Warning);
+        assert_eq!(warnings[0].message, "Unused import `math.sub`");
+        assert_eq!(warnings[0].span, Some((22, 25)));
+    }
+
+    #[test]
+    fn type_only_imports_are_used() {
+        let code = "use types geo.point geo.line status.status_t\n\
+                    fn f(p:point) null { x: line = p return status_t.ON }";
+        let (imports, content) = fn_program::module(code).unwrap();
+        let warnings = check_unused_imports(&imports, &content);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+}
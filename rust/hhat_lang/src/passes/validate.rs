@@ -0,0 +1,95 @@
+//! Validation of a module's content before it is lowered to HIR.
+//!
+
+use std::collections::HashSet;
+use crate::ir::hir::{Content, ContentKind, GroupsDef, Symbol, TypeDef};
+use crate::parse::parser::fn_program;
+use crate::utils::errors::ModuleError;
+
+
+/// Check that all top-level items in `source` belong to the same content
+/// category (constants, types or groups) and return that category.
+///
+/// A parsed [`Content`](crate::ir::hir::Content) holds a single category by
+/// construction, so mixed files are caught here, on the source, instead.
+pub fn validate_content(source: &str) -> Result<ContentKind, ModuleError> {
+    let kinds = fn_program::item_kinds(source)?;
+    let Some(first) = kinds.first() else {
+        return Err(ModuleError::EmptyContent);
+    };
+    if kinds.iter().any(|k| k != first) {
+        return Err(ModuleError::MixedContent);
+    }
+    Ok(*first)
+}
+
+
+/// Report a [`ModuleError::DuplicateDefinition`] for each definition whose name
+/// was already defined in the module. Names with different backend kinds
+/// (`q0` and `@q0`) are distinct.
+pub fn check_duplicates(content: &Content) -> Vec<ModuleError> {
+    let names: Vec<&Symbol> = match content {
+        Content::Consts(consts) => consts.iter().map(|c| &c.name).collect(),
+        Content::Types(types) => types.iter()
+            .filter_map(|ty| match ty {
+                TypeDef::PrimitiveDef(_) => None,
+                TypeDef::StructDef(def) => Some(&def.name),
+                TypeDef::EnumDef { name, .. } | TypeDef::NamedType { name } => Some(name),
+            })
+            .collect(),
+        Content::Groups(groups) => groups.iter()
+            .map(|group| match group {
+                GroupsDef::FnDef(def) => &def.name,
+                GroupsDef::ModifierDef(def) => &def.name,
+                GroupsDef::MetaFnDef(def) => &def.name,
+            })
+            .collect(),
+    };
+    let mut seen = HashSet::new();
+    names.into_iter()
+        .map(|sym| sym.to_string())
+        .filter(|name| !seen.insert(name.clone()))
+        .map(|name| ModuleError::DuplicateDefinition { name })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::ContentKind;
+    use crate::parse::parser::fn_program;
+    use crate::passes::validate::{check_duplicates, validate_content};
+    use crate::utils::errors::ModuleError;
+
+    #[test]
+    fn single_kind_content() {
+        let code = "use fns a.b\nfn f() i32 { return b() }\n<mut> fn g() i32 { fn }";
+        assert_eq!(validate_content(code).unwrap(), ContentKind::Groups);
+        assert_eq!(validate_content("const PI: f64 const E: f64").unwrap(), ContentKind::Consts);
+        let code = "struct p { x:i32 }\n# fn in a comment\nenum s { ON OFF }";
+        assert_eq!(validate_content(code).unwrap(), ContentKind::Types);
+        let code = "fn f() null { print(\"} const\") }";
+        assert_eq!(validate_content(code).unwrap(), ContentKind::Groups);
+    }
+
+    #[test]
+    fn mixed_content() {
+        let code = "const PI: f64\nfn area(r:f64) f64 { return mul(PI r) }";
+        assert!(matches!(validate_content(code), Err(ModuleError::MixedContent)));
+        assert!(matches!(validate_content("# nothing here"), Err(ModuleError::EmptyContent)));
+    }
+
+    #[test]
+    fn no_duplicates() {
+        let (_, content) = fn_program::module("fn f() i32 {} fn g() i32 {} fn @f() i32 {}").unwrap();
+        assert!(check_duplicates(&content).is_empty());
+    }
+
+    #[test]
+    fn duplicate_functions() {
+        let (_, content) = fn_program::module("fn f() i32 {} fn g() i32 {} fn f(a:i32) i32 {}").unwrap();
+        let errors = check_duplicates(&content);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ModuleError::DuplicateDefinition { name } if name == "f"));
+    }
+}
@@ -0,0 +1,91 @@
+//! Lexical environment for the interpreter.
+//!
+
+use std::collections::HashMap;
+use crate::runtime::interpreter::RuntimeError;
+use crate::runtime::value::Value;
+
+
+/// Variable bindings of a scope, chained to the bindings of its parent scope.
+///
+/// Lookups and updates fall through to the parent when a name is not bound
+/// in the current frame, while definitions always go to the current frame.
+///
+#[derive(Default)]
+pub struct Env {
+    vars: HashMap<String, Value>,
+    parent: Option<Box<Env>>,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nested scope whose parent is this environment.
+    pub fn child(self) -> Env {
+        Env { vars: HashMap::new(), parent: Some(Box::new(self)) }
+    }
+
+    /// Drop the current frame, giving back the parent environment.
+    pub fn into_parent(self) -> Option<Env> {
+        self.parent.map(|parent| *parent)
+    }
+
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.vars.insert(String::from(name), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        match self.vars.get(name) {
+            Some(value) => Some(value),
+            None => self.parent.as_ref().and_then(|parent| parent.get(name)),
+        }
+    }
+
+    /// Update an existing binding, in whichever frame it was defined.
+    pub fn set(&mut self, name: &str, value: Value) -> Result<(), RuntimeError> {
+        if let Some(current) = self.vars.get_mut(name) {
+            *current = value;
+            return Ok(());
+        }
+        match self.parent.as_mut() {
+            Some(parent) => parent.set(name, value),
+            None => Err(RuntimeError::UndefinedVariable(String::from(name))),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::env::Env;
+    use crate::runtime::interpreter::RuntimeError;
+    use crate::runtime::value::Value;
+
+    #[test]
+    fn inner_define_does_not_escape() {
+        let mut env = Env::new().child();
+        env.define("x", Value::Int(1));
+        assert_eq!(env.get("x"), Some(&Value::Int(1)));
+        let env = env.into_parent().unwrap();
+        assert_eq!(env.get("x"), None);
+    }
+
+    #[test]
+    fn get_and_set_fall_through_to_parent() {
+        let mut env = Env::new();
+        env.define("x", Value::Int(1));
+        let mut env = env.child();
+        assert_eq!(env.get("x"), Some(&Value::Int(1)));
+        env.set("x", Value::Int(2)).unwrap();
+        let env = env.into_parent().unwrap();
+        assert_eq!(env.get("x"), Some(&Value::Int(2)));
+    }
+
+    #[test]
+    fn set_undefined_errors() {
+        let mut env = Env::new();
+        assert!(matches!(env.set("y", Value::Bool(true)), Err(RuntimeError::UndefinedVariable(n)) if n == "y"));
+    }
+}
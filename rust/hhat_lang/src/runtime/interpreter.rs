@@ -0,0 +1,261 @@
+//! Tree-walking interpreter for CPU (strict) execution of HIR.
+//!
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use crate::ir::hir::{Assign, Block, Expr, Literal, Stmt, Symbol};
+use crate::runtime::env::Env;
+use crate::runtime::value::Value;
+use crate::utils::errors::SemanticError;
+
+
+/// Errors raised while evaluating code.
+///
+#[derive(Debug)]
+pub enum RuntimeError {
+    UndefinedVariable(String),
+    TypeMismatch { expected: String, found: String },
+    DivideByZero,
+    IntegerOverflow,
+    NotCallable(String),
+    Unsupported(String),
+    Semantic(SemanticError),
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::UndefinedVariable(name) => write!(f, "Undefined variable `{}`", name),
+            RuntimeError::TypeMismatch { expected, found } => {
+                write!(f, "Type mismatch: expected {}, found {}", expected, found)
+            }
+            RuntimeError::DivideByZero => write!(f, "Division by zero"),
+            RuntimeError::IntegerOverflow => write!(f, "Integer overflow"),
+            RuntimeError::NotCallable(name) => write!(f, "`{}` is not callable", name),
+            RuntimeError::Unsupported(what) => write!(f, "Unsupported {}", what),
+            RuntimeError::Semantic(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for RuntimeError {}
+
+impl From<SemanticError> for RuntimeError {
+    fn from(err: SemanticError) -> Self {
+        RuntimeError::Semantic(err)
+    }
+}
+
+
+/// Functions provided by the interpreter.
+pub const BUILTINS: &[&str] = &["add", "sub", "mul", "div"];
+
+
+/// Evaluates blocks of statements on the CPU, keeping variable bindings
+/// by symbol name.
+///
+#[derive(Default)]
+pub struct Interpreter {
+    env: Env,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evaluate each statement in order in a child scope, returning the
+    /// value of the first `return` reached, if any.
+    pub fn eval_block(&mut self, block: &Block) -> Result<Option<Value>, RuntimeError> {
+        self.env = std::mem::take(&mut self.env).child();
+        let res = self.eval_stmts(block);
+        self.env = std::mem::take(&mut self.env).into_parent().unwrap_or_default();
+        res
+    }
+
+    fn eval_stmts(&mut self, block: &Block) -> Result<Option<Value>, RuntimeError> {
+        for stmt in block {
+            if let Some(value) = self.eval_stmt(stmt)? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Evaluate a statement, returning a value only for `return`.
+    pub fn eval_stmt(&mut self, stmt: &Stmt) -> Result<Option<Value>, RuntimeError> {
+        match stmt {
+            Stmt::Declare { .. } => Ok(None),
+            Stmt::DeclareAssign { name, value, .. } => {
+                let value = self.eval_expr(value)?;
+                self.env.define(&name.to_string(), value);
+                Ok(None)
+            }
+            Stmt::Assign(Assign::Single { name, value, .. }) => {
+                let value = self.eval_expr(value)?;
+                self.env.set(&name.to_string(), value)?;
+                Ok(None)
+            }
+            Stmt::Expr(expr) => {
+                self.eval_expr(expr)?;
+                Ok(None)
+            }
+            Stmt::Return(expr) => Ok(Some(self.eval_expr(expr)?)),
+            Stmt::Assign(_) => Err(RuntimeError::Unsupported(String::from("assignment"))),
+        }
+    }
+
+    pub fn eval_expr(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        match expr {
+            Expr::Literal(literal) => Ok(Interpreter::eval_literal(literal)),
+            Expr::Id(sym) => self.lookup(sym),
+            Expr::Call { callee, args, .. } => {
+                let args = args.iter()
+                    .map(|arg| self.eval_expr(arg))
+                    .collect::<Result<Vec<Value>, RuntimeError>>()?;
+                Interpreter::eval_call(callee, &args)
+            }
+            Expr::MetaCall(_) => Err(RuntimeError::Unsupported(String::from("meta-function call"))),
+            Expr::Cast { .. } => Err(RuntimeError::Unsupported(String::from("cast"))),
+            Expr::DataMemberAccess(_) => {
+                Err(RuntimeError::Unsupported(String::from("member access")))
+            }
+        }
+    }
+
+    /// Evaluate the arithmetic [`BUILTINS`].
+    pub(crate) fn eval_call(callee: &Symbol, args: &[Value]) -> Result<Value, RuntimeError> {
+        let name = callee.to_string();
+        let op = match name.as_str() {
+            op if BUILTINS.contains(&op) => op,
+            _ => return Err(RuntimeError::NotCallable(name)),
+        };
+        let [lhs, rhs] = args else {
+            return Err(RuntimeError::Unsupported(format!("{} with {} arguments", op, args.len())));
+        };
+        match (lhs, rhs) {
+            (Value::Int(a), Value::Int(b)) => Interpreter::int_arith(op, *a, *b),
+            (Value::Int(a), Value::Float(b)) => Ok(Interpreter::float_arith(op, *a as f64, *b)),
+            (Value::Float(a), Value::Int(b)) => Ok(Interpreter::float_arith(op, *a, *b as f64)),
+            (Value::Float(a), Value::Float(b)) => Ok(Interpreter::float_arith(op, *a, *b)),
+            (Value::Int(_) | Value::Float(_), other) | (other, _) => Err(RuntimeError::TypeMismatch {
+                expected: String::from("int or float"),
+                found: Interpreter::type_of(other),
+            }),
+        }
+    }
+
+    fn int_arith(op: &str, a: i64, b: i64) -> Result<Value, RuntimeError> {
+        let res = match op {
+            "add" => a.checked_add(b),
+            "sub" => a.checked_sub(b),
+            "mul" => a.checked_mul(b),
+            _ if b == 0 => return Err(RuntimeError::DivideByZero),
+            _ => a.checked_div(b),
+        };
+        res.map(Value::Int).ok_or(RuntimeError::IntegerOverflow)
+    }
+
+    fn float_arith(op: &str, a: f64, b: f64) -> Value {
+        Value::Float(match op {
+            "add" => a + b,
+            "sub" => a - b,
+            "mul" => a * b,
+            _ => a / b,
+        })
+    }
+
+    fn type_of(value: &Value) -> String {
+        String::from(match value {
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Str(_) => "str",
+        })
+    }
+
+    pub(crate) fn eval_literal(literal: &Literal) -> Value {
+        Value::from(literal.clone())
+    }
+
+    fn lookup(&self, sym: &Symbol) -> Result<Value, RuntimeError> {
+        let name = sym.to_string();
+        self.env.get(&name)
+            .cloned()
+            .ok_or(RuntimeError::UndefinedVariable(name))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::{Expr, MetaCall, Stmt};
+    use crate::parse::parser::fn_program;
+    use crate::runtime::interpreter::{Interpreter, RuntimeError};
+    use crate::runtime::value::Value;
+
+    #[test]
+    fn eval_declare_and_return() {
+        let block = fn_program::block("{ x: i32 = 2; return x }").unwrap();
+        let value = Interpreter::new().eval_block(&block).unwrap();
+        assert_eq!(value, Some(Value::Int(2)));
+    }
+
+    #[test]
+    fn eval_block_runs_in_child_scope() {
+        let Ok(Stmt::Expr(Expr::MetaCall(MetaCall::Bdn { body, .. }))) = fn_program::stmt("loop(x){ y: i32 = 2 }") else {
+            panic!("expected body meta-call");
+        };
+        let mut interpreter = Interpreter::new();
+        assert_eq!(interpreter.eval_block(&body).unwrap(), None);
+        let y = fn_program::expr("y").unwrap();
+        assert!(matches!(interpreter.eval_expr(&y), Err(RuntimeError::UndefinedVariable(_))));
+    }
+
+    #[test]
+    fn eval_undefined_variable() {
+        let block = fn_program::block("{ return y }").unwrap();
+        let err = Interpreter::new().eval_block(&block).unwrap_err();
+        assert!(matches!(err, RuntimeError::UndefinedVariable(name) if name == "y"));
+    }
+
+    fn eval(code: &str) -> Result<Option<Value>, RuntimeError> {
+        Interpreter::new().eval_block(&fn_program::block(code).unwrap())
+    }
+
+    #[test]
+    fn eval_reassign() {
+        assert_eq!(eval("{ x: i32 = 1 x = add(x 1) return x }").unwrap(), Some(Value::Int(2)));
+        assert!(matches!(eval("{ x = 1 }"), Err(RuntimeError::UndefinedVariable(name)) if name == "x"));
+    }
+
+    #[test]
+    fn eval_arithmetic() {
+        assert_eq!(eval("{ return add(2 3) }").unwrap(), Some(Value::Int(5)));
+        assert_eq!(eval("{ return sub(2 3) }").unwrap(), Some(Value::Int(-1)));
+        assert_eq!(eval("{ return mul(4 add(1 2)) }").unwrap(), Some(Value::Int(12)));
+        assert_eq!(eval("{ return div(7 2) }").unwrap(), Some(Value::Int(3)));
+        assert_eq!(eval("{ return add(1 0.5) }").unwrap(), Some(Value::Float(1.5)));
+        assert_eq!(eval("{ return div(1.0 4) }").unwrap(), Some(Value::Float(0.25)));
+    }
+
+    #[test]
+    fn eval_arithmetic_errors() {
+        assert!(matches!(eval("{ return div(1 0) }"), Err(RuntimeError::DivideByZero)));
+        assert!(matches!(eval("{ return add(9223372036854775807 1) }"), Err(RuntimeError::IntegerOverflow)));
+        assert!(matches!(
+            eval("{ return add(1 \"a\") }"),
+            Err(RuntimeError::TypeMismatch { found, .. }) if found == "str"
+        ));
+        assert!(matches!(eval("{ return print(1) }"), Err(RuntimeError::NotCallable(name)) if name == "print"));
+    }
+
+    #[test]
+    fn runtime_error_display() {
+        assert_eq!(RuntimeError::DivideByZero.to_string(), "Division by zero");
+        assert_eq!(RuntimeError::UndefinedVariable(String::from("y")).to_string(), "Undefined variable `y`");
+        assert_eq!(RuntimeError::NotCallable(String::from("x")).to_string(), "`x` is not callable");
+        let err = RuntimeError::TypeMismatch { expected: String::from("int"), found: String::from("str") };
+        assert_eq!(err.to_string(), "Type mismatch: expected int, found str");
+    }
+}
@@ -0,0 +1,4 @@
+pub(crate) mod env;
+pub(crate) mod interpreter;
+pub(crate) mod staged;
+pub(crate) mod value;
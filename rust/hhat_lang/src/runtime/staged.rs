@@ -0,0 +1,191 @@
+//! Staged (lazy) execution of QPU code.
+//!
+//! QPU calls are not run as they are reached: they are recorded into a plan
+//! that a backend emits in one go once the plan is complete.
+//!
+
+use crate::ir::hir::{Block, Expr, Literal, MetaCall, Modifier, Stmt, Symbol};
+use crate::ir::ids::BackendKind;
+use crate::runtime::interpreter::{Interpreter, RuntimeError};
+use crate::runtime::value::Value;
+use crate::utils::errors::SemanticError;
+
+
+/// Number of times a plan runs when no `<shots=N>` modifier is given.
+pub const DEFAULT_SHOTS: u32 = 1;
+
+
+/// A QPU gate applied to its qubit operands, ex: `@cx(@q0 @q1)`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedOp {
+    pub gate: String,
+    pub qubits: Vec<usize>,
+}
+
+
+/// Ordered QPU operations collected while walking code. CPU code met along
+/// the way is evaluated right away by an [`Interpreter`].
+///
+/// `shots` holds the value of the `<shots=N>` modifier found on QPU calls
+/// and meta-calls, if any.
+///
+#[derive(Default)]
+pub struct StagedPlan {
+    pub shots: Option<u32>,
+    ops: Vec<PlannedOp>,
+    interpreter: Interpreter,
+}
+
+impl StagedPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stage the QPU calls of `block` and run the rest of its statements,
+    /// up to the first `return`, whose value is returned.
+    pub fn stage_block(&mut self, block: &Block) -> Result<Option<Value>, RuntimeError> {
+        for stmt in block {
+            if let Some(value) = self.stage_stmt(stmt)? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Stage a statement if it is a QPU call, or evaluate it otherwise.
+    pub fn stage_stmt(&mut self, stmt: &Stmt) -> Result<Option<Value>, RuntimeError> {
+        match stmt {
+            Stmt::Expr(Expr::Call { callee, args, modifiers }) if callee.backend_kind == BackendKind::QPU => {
+                self.stage_call(callee, args, modifiers)?;
+                Ok(None)
+            }
+            Stmt::Expr(Expr::MetaCall(MetaCall::Bdn { name, body, modifiers, .. }))
+                if name.backend_kind == BackendKind::QPU =>
+            {
+                self.record_shots(modifiers)?;
+                self.stage_block(body)?;
+                Ok(None)
+            }
+            _ => self.interpreter.eval_stmt(stmt),
+        }
+    }
+
+    /// Record a QPU gate call, ex: `@cx(@q0 @q1)`, along with its `shots`
+    /// modifier, if any.
+    pub(crate) fn stage_call(
+        &mut self,
+        callee: &Symbol,
+        args: &[Expr],
+        modifiers: &[Modifier],
+    ) -> Result<(), RuntimeError> {
+        self.record_shots(modifiers)?;
+        let qubits = args.iter()
+            .map(|arg| match arg {
+                Expr::Id(sym) => sym.qubit_index(),
+                _ => None,
+            }.ok_or_else(|| RuntimeError::TypeMismatch {
+                expected: String::from("qubit"),
+                found: arg.to_string(),
+            }))
+            .collect::<Result<Vec<usize>, RuntimeError>>()?;
+        self.ops.push(PlannedOp { gate: callee.value.clone(), qubits });
+        Ok(())
+    }
+
+    /// Keep the value of a `<shots=N>` modifier, failing if the plan already
+    /// has a different one.
+    pub(crate) fn record_shots(&mut self, modifiers: &[Modifier]) -> Result<(), RuntimeError> {
+        for modifier in modifiers.iter().filter(|m| m.name.value == "shots") {
+            let shots = match &modifier.value {
+                Some(Expr::Literal(Literal::Int(n, _, _))) => u32::try_from(*n).ok().filter(|n| *n > 0),
+                _ => None,
+            }.ok_or_else(|| RuntimeError::TypeMismatch {
+                expected: String::from("positive u32 shots"),
+                found: modifier.to_string(),
+            })?;
+            match self.shots {
+                Some(first) if first != shots => {
+                    return Err(SemanticError::ConflictingShots { first, second: shots }.into());
+                }
+                _ => self.shots = Some(shots),
+            }
+        }
+        Ok(())
+    }
+
+    /// The staged operations, in the order they were reached.
+    pub fn finalize(&self) -> Vec<PlannedOp> {
+        self.ops.clone()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::parser::fn_program;
+    use crate::runtime::interpreter::RuntimeError;
+    use crate::runtime::staged::{PlannedOp, StagedPlan};
+    use crate::runtime::value::Value;
+    use crate::utils::errors::SemanticError;
+
+    #[test]
+    fn stage_qpu_gates() {
+        let block = fn_program::block("{ @h(@q0) x: i64 = add(1 2) @cx(@q0 @q1) }").unwrap();
+        let mut plan = StagedPlan::new();
+        plan.stage_block(&block).unwrap();
+        let ops = plan.finalize();
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[0], PlannedOp { gate: String::from("h"), qubits: vec![0] });
+        assert_eq!(ops[1], PlannedOp { gate: String::from("cx"), qubits: vec![0, 1] });
+    }
+
+    #[test]
+    fn stage_runs_cpu_code_eagerly() {
+        let block = fn_program::block("{ @h(@q0) return add(1 2) @x(@q0) }").unwrap();
+        let mut plan = StagedPlan::new();
+        assert_eq!(plan.stage_block(&block).unwrap(), Some(Value::Int(3)));
+        assert_eq!(plan.finalize().len(), 1);
+
+        let block = fn_program::block("{ @h(@q0) x: i64 = div(1 0) }").unwrap();
+        let err = StagedPlan::new().stage_block(&block).unwrap_err();
+        assert!(matches!(err, RuntimeError::DivideByZero));
+
+        let block = fn_program::block("{ @h(x) }").unwrap();
+        let err = StagedPlan::new().stage_block(&block).unwrap_err();
+        assert!(matches!(err, RuntimeError::TypeMismatch { found, .. } if found == "x"));
+    }
+
+    #[test]
+    fn stage_shots() {
+        let block = fn_program::block("{ @h(@q0)<shots=2048> @x(@q1) }").unwrap();
+        let mut plan = StagedPlan::new();
+        plan.stage_block(&block).unwrap();
+        assert_eq!(plan.shots, Some(2048));
+
+        let block = fn_program::block("{ @run(@q0)<shots=10>{ @h(@q0) } }").unwrap();
+        let mut plan = StagedPlan::new();
+        plan.stage_block(&block).unwrap();
+        assert_eq!(plan.shots, Some(10));
+        assert_eq!(plan.finalize().len(), 1);
+
+        let block = fn_program::block("{ @h(@q0) }").unwrap();
+        let mut plan = StagedPlan::new();
+        plan.stage_block(&block).unwrap();
+        assert_eq!(plan.shots, None);
+    }
+
+    #[test]
+    fn stage_conflicting_shots() {
+        let block = fn_program::block("{ @h(@q0)<shots=100> @x(@q0)<shots=100> @x(@q1)<shots=200> }").unwrap();
+        let err = StagedPlan::new().stage_block(&block).unwrap_err();
+        assert!(matches!(
+            err,
+            RuntimeError::Semantic(SemanticError::ConflictingShots { first: 100, second: 200 })
+        ));
+
+        let block = fn_program::block("{ @h(@q0)<shots=x> }").unwrap();
+        let err = StagedPlan::new().stage_block(&block).unwrap_err();
+        assert!(matches!(err, RuntimeError::TypeMismatch { .. }));
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt::{Display, Formatter};
+use crate::ir::hir::Literal;
+
+
+/// Runtime value for the CPU interpreter.
+///
+/// Mirrors [`Literal`], without the backend kind.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl From<Literal> for Value {
+    fn from(literal: Literal) -> Self {
+        match literal {
+            Literal::Bool(v, _, _) => Value::Bool(v),
+            Literal::Int(v, _, _) => Value::Int(v),
+            Literal::Float(v, _, _) => Value::Float(v),
+            Literal::Str(v, _, _) => Value::Str(v),
+        }
+    }
+}
+
+/// Prints the value as shown to the user, with strings quoted.
+///
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{:?}", v),
+            Value::Str(v) => write!(f, "{:?}", v),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::Literal;
+    use crate::ir::ids::BackendKind;
+    use crate::runtime::value::Value;
+
+    #[test]
+    fn from_literal() {
+        assert_eq!(Value::from(Literal::Bool(true, BackendKind::CPU, None)), Value::Bool(true));
+        assert_eq!(Value::from(Literal::Int(-3, BackendKind::QPU, None)), Value::Int(-3));
+        assert_eq!(Value::from(Literal::Float(2.5, BackendKind::CPU, Some((0, 3)))), Value::Float(2.5));
+        assert_eq!(
+            Value::from(Literal::Str(String::from("hoi"), BackendKind::CPU, None)),
+            Value::Str(String::from("hoi"))
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Value::Bool(false).to_string(), "false");
+        assert_eq!(Value::Int(42).to_string(), "42");
+        assert_eq!(Value::Float(1.0).to_string(), "1.0");
+        assert_eq!(Value::Str(String::from("say \"hi\"")).to_string(), r#""say \"hi\"""#);
+    }
+}
@@ -0,0 +1,87 @@
+//! Function call checks against the called function's definition.
+//!
+
+use crate::ir::hir::{Content, Expr, FnDef};
+use crate::semantics::infer::{infer_type, literal_fits};
+use crate::utils::errors::SemanticError;
+
+
+/// Check a call's arguments against `fn_def`'s parameters: the number of
+/// arguments must match, and every argument whose type can be inferred
+/// (see [`infer_type`]) must have the parameter's type, or be a literal
+/// fitting it (see [`literal_fits`]).
+///
+/// Anything other than an [`Expr::Call`] is not checked.
+///
+pub fn check_call(call: &Expr, fn_def: &FnDef, content: &Content) -> Vec<SemanticError> {
+    let Expr::Call { args, .. } = call else {
+        return vec![];
+    };
+    if args.len() != fn_def.params.len() {
+        return vec![SemanticError::ArityMismatch {
+            expected: fn_def.params.len(),
+            found: args.len(),
+        }];
+    }
+    args.iter()
+        .zip(&fn_def.params)
+        .filter_map(|(arg, param)| {
+            let found = infer_type(arg, content)?.name.to_string();
+            let expected = param.ty.name.to_string();
+            (found != expected && !literal_fits(arg, &param.ty)).then(|| SemanticError::ArgTypeMismatch {
+                param: param.name.to_string(),
+                expected,
+                found,
+                span: match arg {
+                    Expr::Literal(lit) => lit.span(),
+                    _ => None,
+                },
+            })
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::Content;
+    use crate::parse::parser::fn_program;
+    use crate::semantics::calls::check_call;
+    use crate::utils::errors::SemanticError;
+
+    const SQUARE: &str = "fn square(n: i64 scale: f64) f64 { return mul(n scale) }";
+
+    #[test]
+    fn correct_call() {
+        let fn_def = fn_program::fn_def(SQUARE).unwrap();
+        let call = fn_program::expr("square(2 1.5)").unwrap();
+        assert!(check_call(&call, &fn_def, &Content::Groups(vec![])).is_empty());
+    }
+
+    #[test]
+    fn literal_args_fit_narrower_types() {
+        let fn_def = fn_program::fn_def("fn h(a:i32 b:f32) i32 { return a }").unwrap();
+        let call = fn_program::expr("h(2 1.5)").unwrap();
+        assert!(check_call(&call, &fn_def, &Content::Groups(vec![])).is_empty());
+    }
+
+    #[test]
+    fn too_few_args() {
+        let fn_def = fn_program::fn_def(SQUARE).unwrap();
+        let call = fn_program::expr("square(2)").unwrap();
+        let errors = check_call(&call, &fn_def, &Content::Groups(vec![]));
+        assert!(matches!(errors[..], [SemanticError::ArityMismatch { expected: 2, found: 1 }]));
+    }
+
+    #[test]
+    fn mismatched_arg_type() {
+        let fn_def = fn_program::fn_def(SQUARE).unwrap();
+        let call = fn_program::expr("square(2.0 1.5)").unwrap();
+        let errors = check_call(&call, &fn_def, &Content::Groups(vec![]));
+        let [SemanticError::ArgTypeMismatch { param, expected, found, span }] = &errors[..] else {
+            panic!("expected a single argument type mismatch");
+        };
+        assert_eq!((param.as_str(), expected.as_str(), found.as_str()), ("n", "i64", "f64"));
+        assert_eq!(*span, Some((7, 10)));
+    }
+}
@@ -0,0 +1,137 @@
+//! Cast checks: which type conversions `data * type` may perform.
+//!
+
+use crate::ir::hir::{Content, Expr, GroupsDef, PrimitiveDef, TypeName};
+use crate::ir::ids::BackendKind;
+use crate::ir::visit::ExprVisitor;
+use crate::semantics::infer::{infer_type, literal_fits};
+use crate::utils::errors::SemanticError;
+
+
+/// Check that a value of type `value_ty` can be cast to `to_ty`.
+///
+/// Among primitive types, a value can be cast to its own type, to a wider
+/// integer able to hold all of its values, or from an integer or float to a
+/// float (`i32` -> `i64`, `u32` -> `i64`, `i32` -> `f64`, `f32` -> `f64`).
+/// Casts from QPU to CPU are not supported yet. Casts involving other types
+/// are left to their cast functions and not checked here.
+///
+pub fn check_cast(value_ty: &TypeName, to_ty: &TypeName) -> Result<(), SemanticError> {
+    let invalid = || SemanticError::InvalidCast {
+        from: value_ty.name.to_string(),
+        to: to_ty.name.to_string(),
+        span: None,
+    };
+    if value_ty.name.backend_kind == BackendKind::QPU && to_ty.name.backend_kind == BackendKind::CPU {
+        return Err(invalid());
+    }
+    let (Some(from), Some(to)) = (
+        PrimitiveDef::from_name(&value_ty.name.value),
+        PrimitiveDef::from_name(&to_ty.name.value),
+    ) else {
+        return Ok(());
+    };
+    if from == to || allowed(&from, &to) {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
+
+fn allowed(from: &PrimitiveDef, to: &PrimitiveDef) -> bool {
+    use PrimitiveDef::*;
+    matches!(
+        (from, to),
+        (U32, U64 | I64) | (I32, I64) | (U32 | U64 | I32 | I64, F32 | F64) | (F32, F64)
+    )
+}
+
+
+/// Check every `data * type` cast in the function bodies of `content` whose
+/// value type can be inferred (see [`infer_type`]). Literals fitting the
+/// target type (see [`literal_fits`]) can always be cast, and errors on
+/// literals point at them.
+///
+pub fn check_casts(content: &Content) -> Vec<SemanticError> {
+    let mut casts = Casts { content, errors: vec![] };
+    if let Content::Groups(groups) = content {
+        for group in groups {
+            if let GroupsDef::FnDef(def) = group {
+                casts.visit_block(&def.body);
+            }
+        }
+    }
+    casts.errors
+}
+
+
+struct Casts<'a> {
+    content: &'a Content,
+    errors: Vec<SemanticError>,
+}
+
+impl ExprVisitor for Casts<'_> {
+    fn visit_cast(&mut self, value: &Expr, to_ty: &TypeName) {
+        if let Some(value_ty) = infer_type(value, self.content)
+            && !literal_fits(value, to_ty)
+            && let Err(mut err) = check_cast(&value_ty, to_ty)
+        {
+            if let (SemanticError::InvalidCast { span, .. }, Expr::Literal(lit)) = (&mut err, value) {
+                *span = lit.span();
+            }
+            self.errors.push(err);
+        }
+        self.visit_expr(value)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::{Content, GroupsDef, Symbol, TypeName};
+    use crate::ir::ids::BackendKind;
+    use crate::parse::parser::fn_program;
+    use crate::semantics::cast::{check_cast, check_casts};
+    use crate::utils::errors::SemanticError;
+
+    fn ty(name: &str, kind: BackendKind) -> TypeName {
+        TypeName { name: Symbol::new(String::from(name), kind), modifiers: vec![] }
+    }
+
+    fn cpu(name: &str) -> TypeName {
+        ty(name, BackendKind::CPU)
+    }
+
+    #[test]
+    fn widening_casts() {
+        assert!(check_cast(&cpu("i32"), &cpu("i64")).is_ok());
+        assert!(check_cast(&cpu("u32"), &cpu("i64")).is_ok());
+        assert!(check_cast(&cpu("i32"), &cpu("f64")).is_ok());
+        assert!(check_cast(&cpu("f32"), &cpu("f64")).is_ok());
+        assert!(check_cast(&cpu("str"), &cpu("str")).is_ok());
+        assert!(check_cast(&cpu("point"), &cpu("str")).is_ok());
+    }
+
+    #[test]
+    fn invalid_casts() {
+        let err = check_cast(&cpu("str"), &cpu("i32")).unwrap_err();
+        assert!(matches!(err, SemanticError::InvalidCast { from, to, .. } if from == "str" && to == "i32"));
+        assert!(check_cast(&cpu("i64"), &cpu("i32")).is_err());
+        assert!(check_cast(&cpu("f64"), &cpu("f32")).is_err());
+        assert!(check_cast(&cpu("bool"), &cpu("u32")).is_err());
+        let err = check_cast(&ty("u32", BackendKind::QPU), &cpu("u32")).unwrap_err();
+        assert!(matches!(err, SemanticError::InvalidCast { from, .. } if from == "@u32"));
+    }
+
+    #[test]
+    fn casts_in_functions() {
+        let code = r#"fn f(v:i32) f64 { x: u64 = 1 * u64 y: i32 = "7" * i32 return v * f64 }"#;
+        let content = Content::Groups(vec![GroupsDef::FnDef(fn_program::fn_def(code).unwrap())]);
+        let errors = check_casts(&content);
+        let [SemanticError::InvalidCast { from, span, .. }] = &errors[..] else {
+            panic!("expected a single invalid cast");
+        };
+        assert_eq!(from, "str");
+        assert_eq!(*span, Some((44, 47)));
+    }
+}
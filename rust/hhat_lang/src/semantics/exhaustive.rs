@@ -0,0 +1,95 @@
+//! Exhaustiveness of option (cases) meta-calls over enums.
+//!
+
+use crate::ir::hir::{EnumMember, Expr, MetaCall, OptionBody, TypeDef};
+use crate::utils::errors::SemanticError;
+
+
+/// Option name matching any case not listed before it.
+pub const WILDCARD: &str = "default";
+
+
+/// Check that the options of an [`MetaCall::Optn`] (or [`MetaCall::OptBdn`])
+/// over a value of type `enum_def` cover every member of the enum, either
+/// by name (`ON`, `status_t.ON`) or through a [`WILDCARD`] option.
+///
+/// Other meta-calls and type definitions are not checked.
+///
+pub fn check_exhaustive(call: &MetaCall, enum_def: &TypeDef) -> Vec<SemanticError> {
+    let options = match call {
+        MetaCall::Optn { options, .. } => options,
+        MetaCall::OptBdn { body, .. } => body,
+        MetaCall::Bdn { .. } => return vec![],
+    };
+    let TypeDef::EnumDef { members, .. } = enum_def else {
+        return vec![];
+    };
+    let covered: Vec<&str> = options.iter().filter_map(option_name).collect();
+    if covered.contains(&WILDCARD) {
+        return vec![];
+    }
+    let missing: Vec<String> = members.iter()
+        .map(|m| match m {
+            EnumMember::KindMember(name) => &name.value,
+            EnumMember::StructMember(def) => &def.name.value,
+        })
+        .filter(|name| !covered.contains(&name.as_str()))
+        .cloned()
+        .collect();
+    if missing.is_empty() {
+        vec![]
+    } else {
+        vec![SemanticError::NonExhaustiveMatch { missing }]
+    }
+}
+
+/// Enum member named by an option: the last symbol of `status_t.ON`, or `ON`.
+fn option_name(option: &OptionBody) -> Option<&str> {
+    match &option.opt {
+        Expr::Id(sym) => Some(&sym.value),
+        Expr::DataMemberAccess(path) => path.value.last().map(|sym| sym.value.as_str()),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::{Expr, TypeDef};
+    use crate::parse::parser::{fn_program, type_program};
+    use crate::semantics::exhaustive::check_exhaustive;
+    use crate::utils::errors::SemanticError;
+
+    fn check(call: &str) -> Vec<SemanticError> {
+        let status = type_program::enum_def("enum status_t { ON OFF }").unwrap();
+        let Ok(Expr::MetaCall(call)) = fn_program::expr(call) else {
+            panic!("expected meta-call");
+        };
+        check_exhaustive(&call, &status)
+    }
+
+    #[test]
+    fn missing_member() {
+        let errors = check("match(status_t.ON:{ return 1 })");
+        assert!(matches!(
+            &errors[..],
+            [SemanticError::NonExhaustiveMatch { missing }] if missing == &["OFF"]
+        ));
+    }
+
+    #[test]
+    fn exhaustive_options() {
+        assert!(check("match(ON:{ return 1 } status_t.OFF:{ return 0 })").is_empty());
+        assert!(check("match(ON:{ return 1 } default:{ return 0 })").is_empty());
+        assert_eq!(check("switch(s){ OFF:{ return 0 } }").len(), 1);
+    }
+
+    #[test]
+    fn non_enum_type() {
+        let point = TypeDef::StructDef(type_program::struct_def("struct point { x:i32 }").unwrap());
+        let Ok(Expr::MetaCall(call)) = fn_program::expr("match(x:{ return 1 })") else {
+            panic!("expected meta-call");
+        };
+        assert!(check_exhaustive(&call, &point).is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+//! Type inference for declarations without an explicit type.
+//!
+
+use crate::ir::hir::{Content, Expr, GroupsDef, Literal, PrimitiveDef, Stmt, Symbol, TypeName};
+use crate::ir::ids::BackendKind;
+use crate::utils::errors::SemanticError;
+
+
+/// Infer the type of an expression from its literal kind or, for calls,
+/// from the return type of the function with the callee's name in `content`.
+///
+/// Returns `None` when the expression cannot be resolved to a type.
+///
+pub fn infer_type(expr: &Expr, content: &Content) -> Option<TypeName> {
+    match expr {
+        Expr::Literal(lit) => {
+            let (name, backend_kind) = match lit {
+                Literal::Int(_, kind, _) => ("i64", kind),
+                Literal::Float(_, kind, _) => ("f64", kind),
+                Literal::Bool(_, kind, _) => ("bool", kind),
+                Literal::Str(_, kind, _) => ("str", kind),
+            };
+            Some(type_name(name, *backend_kind))
+        }
+        Expr::Call { callee, .. } => {
+            let Content::Groups(groups) = content else {
+                return None;
+            };
+            groups.iter().find_map(|group| match group {
+                GroupsDef::FnDef(def) if def.name.to_string() == callee.to_string() => {
+                    Some(type_name(&def.ty.name.value, def.ty.name.backend_kind))
+                }
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Whether `expr` is a literal that can take the type `ty` besides the one
+/// [`infer_type`] gives it: integer literals fit any integer primitive, and
+/// float literals `f32` and `f64`, on the same backend.
+///
+pub fn literal_fits(expr: &Expr, ty: &TypeName) -> bool {
+    use PrimitiveDef::*;
+    let primitive = PrimitiveDef::from_name(&ty.name.value);
+    match expr {
+        Expr::Literal(Literal::Int(_, kind, _)) => {
+            *kind == ty.name.backend_kind && matches!(primitive, Some(U32 | U64 | I32 | I64))
+        }
+        Expr::Literal(Literal::Float(_, kind, _)) => {
+            *kind == ty.name.backend_kind && matches!(primitive, Some(F32 | F64))
+        }
+        _ => false,
+    }
+}
+
+/// Fill in the type of a [`Stmt::DeclareAssign`] declared with a
+/// [`TypeName::placeholder`]; other statements are left untouched.
+///
+pub fn infer_declare(stmt: &mut Stmt, content: &Content) -> Result<(), SemanticError> {
+    if let Stmt::DeclareAssign { name, ty, value, .. } = stmt
+        && ty.is_placeholder()
+    {
+        *ty = infer_type(value, content)
+            .ok_or_else(|| SemanticError::CannotInfer { name: name.to_string() })?;
+    }
+    Ok(())
+}
+
+fn type_name(name: &str, backend_kind: BackendKind) -> TypeName {
+    TypeName { name: Symbol::new(String::from(name), backend_kind), modifiers: vec![] }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::{Content, Expr, GroupsDef, Literal, PrimitiveDef, Stmt, Symbol, TypeName};
+    use crate::ir::ids::BackendKind;
+    use crate::parse::parser::fn_program;
+    use crate::semantics::infer::infer_declare;
+    use crate::utils::errors::SemanticError;
+
+    fn infer(value: Expr, content: &Content) -> Result<String, SemanticError> {
+        let mut stmt = Stmt::DeclareAssign {
+            name: Symbol::new(String::from("x"), BackendKind::CPU),
+            ty: TypeName::placeholder(),
+            modifiers: vec![],
+            value,
+        };
+        infer_declare(&mut stmt, content)?;
+        let Stmt::DeclareAssign { ty, .. } = stmt else {
+            panic!("expected declare-assign statement");
+        };
+        Ok(ty.name.value)
+    }
+
+    #[test]
+    fn infer_literals() {
+        let content = Content::Groups(vec![]);
+        assert_eq!(infer(Expr::Literal(Literal::Int(42, BackendKind::CPU, None)), &content).unwrap(), "i64");
+        assert_eq!(infer(Expr::Literal(Literal::Float(1.5, BackendKind::CPU, None)), &content).unwrap(), "f64");
+        assert_eq!(infer(Expr::Literal(Literal::Bool(true, BackendKind::CPU, None)), &content).unwrap(), "bool");
+        assert_eq!(infer(Expr::Literal(Literal::Str(String::from("hi"), BackendKind::CPU, None)), &content).unwrap(), "str");
+    }
+
+    #[test]
+    fn infer_call_return_type() {
+        let fn_def = fn_program::fn_def("fn one() u32 { return 1 }").unwrap();
+        let content = Content::Groups(vec![GroupsDef::FnDef(fn_def)]);
+        assert_eq!(infer(fn_program::expr("one()").unwrap(), &content).unwrap(), "u32");
+        assert!(matches!(infer(fn_program::expr("two()").unwrap(), &content), Err(SemanticError::CannotInfer { name }) if name == "x"));
+    }
+
+    #[test]
+    fn explicit_type_is_kept() {
+        let mut stmt = fn_program::stmt("x: u8 = 42").unwrap();
+        infer_declare(&mut stmt, &Content::Groups(vec![])).unwrap();
+        let Stmt::DeclareAssign { ty, .. } = stmt else {
+            panic!("expected declare-assign statement");
+        };
+        assert_eq!(ty.name.value, "u8");
+    }
+}
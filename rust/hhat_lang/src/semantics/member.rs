@@ -0,0 +1,85 @@
+//! Type resolution of struct member accesses, ex: `p.x`, `line.start.x`.
+//!
+
+use crate::ir::hir::{CompositeSymbol, Content, StructDef, TypeDef, TypeName};
+use crate::utils::errors::SemanticError;
+
+
+/// Resolve the type of the member accessed by `composite`, whose first
+/// segment is a variable of type `base`. Each further segment is looked up
+/// in the members of the struct before it, nested structs being found by
+/// their type name among the definitions in `types`.
+///
+/// An access with no member resolves to `base` itself.
+///
+pub fn resolve_member_access(
+    composite: &CompositeSymbol,
+    base: &StructDef,
+    types: &Content,
+) -> Result<TypeName, SemanticError> {
+    let mut ty = TypeName { name: base.name.clone(), modifiers: base.modifiers.clone() };
+    let mut current = Some(base);
+    for member in composite.value.iter().skip(1) {
+        let no_such_member = || SemanticError::NoSuchMember {
+            ty: ty.name.to_string(),
+            member: member.to_string(),
+        };
+        let found = current
+            .and_then(|def| def.members.iter().find(|m| m.name == *member))
+            .ok_or_else(no_such_member)?;
+        ty = found.ty.clone();
+        current = find_struct(&ty, types);
+    }
+    Ok(ty)
+}
+
+fn find_struct<'a>(ty: &TypeName, types: &'a Content) -> Option<&'a StructDef> {
+    let Content::Types(defs) = types else {
+        return None;
+    };
+    defs.iter().find_map(|def| match def {
+        TypeDef::StructDef(s) if s.name == ty.name => Some(s),
+        _ => None,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::{Content, Expr, TypeDef};
+    use crate::parse::parser::{fn_program, type_program};
+    use crate::semantics::member::resolve_member_access;
+    use crate::utils::errors::SemanticError;
+
+    fn types() -> Content {
+        let (_, content) = type_program::module(
+            "struct point { x:i32 y:i32 } struct line { start:point end:point }"
+        ).unwrap();
+        content
+    }
+
+    fn resolve(access: &str) -> Result<String, SemanticError> {
+        let types = types();
+        let Content::Types(defs) = &types else { unreachable!() };
+        let TypeDef::StructDef(line) = &defs[1] else { unreachable!() };
+        let Ok(Expr::DataMemberAccess(composite)) = fn_program::expr(access) else {
+            panic!("expected a member access from {access}");
+        };
+        resolve_member_access(&composite, line, &types).map(|ty| ty.to_string())
+    }
+
+    #[test]
+    fn nested_access() {
+        assert_eq!(resolve("l.start").unwrap(), "point");
+        assert_eq!(resolve("l.end.y").unwrap(), "i32");
+    }
+
+    #[test]
+    fn unknown_member() {
+        let Err(SemanticError::NoSuchMember { ty, member }) = resolve("l.start.z") else {
+            panic!("expected an unknown member error");
+        };
+        assert_eq!((ty.as_str(), member.as_str()), ("point", "z"));
+        assert!(matches!(resolve("l.end.y.x"), Err(SemanticError::NoSuchMember { .. })));
+    }
+}
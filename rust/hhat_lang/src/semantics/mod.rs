@@ -1 +1,8 @@
-mod store;
\ No newline at end of file
+pub(crate) mod calls;
+pub(crate) mod cast;
+pub(crate) mod exhaustive;
+pub(crate) mod infer;
+pub(crate) mod member;
+pub(crate) mod mode;
+pub(crate) mod returns;
+mod store;
@@ -0,0 +1,105 @@
+//! Execution mode checks.
+//!
+//! Backends that cannot execute instructions immediately (QPU) must only be
+//! called from lazy (staged) code.
+//!
+
+use crate::ir::hir::{Content, Expr, GroupsDef, MetaCall, Symbol};
+use crate::ir::ids::BackendKind;
+use crate::ir::visit::{walk_meta_call, ExprVisitor};
+use crate::utils::errors::SemanticError;
+
+
+/// How the instructions of a program are executed.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// Executed right away.
+    Strict,
+    /// Staged and executed when their result is needed.
+    Lazy,
+}
+
+impl ExecutionMode {
+    /// Mode of code run on `backend`: lazy when it cannot execute strictly.
+    pub fn for_backend(backend: BackendKind) -> Self {
+        if backend.supports_strict() { ExecutionMode::Strict } else { ExecutionMode::Lazy }
+    }
+}
+
+
+/// Check every call and meta-call of `content` can run under `mode`,
+/// returning an error for each QPU callee reached in strict mode.
+///
+pub fn check_execution_mode(content: &Content, mode: ExecutionMode) -> Vec<SemanticError> {
+    let mut checker = ModeChecker { mode, errors: vec![] };
+    if let Content::Groups(groups) = content {
+        for group in groups {
+            match group {
+                GroupsDef::FnDef(def) => checker.visit_block(&def.body),
+                GroupsDef::ModifierDef(def) => checker.visit_block(&def.body),
+                GroupsDef::MetaFnDef(def) => checker.visit_block(&def.body),
+            }
+        }
+    }
+    checker.errors
+}
+
+
+struct ModeChecker {
+    mode: ExecutionMode,
+    errors: Vec<SemanticError>,
+}
+
+impl ModeChecker {
+    fn check_callee(&mut self, callee: &Symbol) {
+        if self.mode == ExecutionMode::Strict && !callee.backend_kind.supports_strict() {
+            self.errors.push(SemanticError::StrictQpuNotAllowed { name: callee.to_string() });
+        }
+    }
+}
+
+impl ExprVisitor for ModeChecker {
+    fn visit_call(&mut self, callee: &Symbol, args: &[Expr]) {
+        self.check_callee(callee);
+        for arg in args {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_meta_call(&mut self, call: &MetaCall) {
+        match call {
+            MetaCall::Optn { name, .. }
+            | MetaCall::Bdn { name, .. }
+            | MetaCall::OptBdn { name, .. } => self.check_callee(name),
+        }
+        walk_meta_call(self, call);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::{Content, GroupsDef};
+    use crate::parse::parser::fn_program;
+    use crate::semantics::mode::{check_execution_mode, ExecutionMode};
+    use crate::utils::errors::SemanticError;
+
+    fn content(code: &str) -> Content {
+        Content::Groups(vec![GroupsDef::FnDef(fn_program::fn_def(code).unwrap())])
+    }
+
+    #[test]
+    fn strict_qpu_call() {
+        let content = content("fn main() null { x: i32 = add(1 2) loop(x){ @h(@q0) } }");
+        let errors = check_execution_mode(&content, ExecutionMode::Strict);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], SemanticError::StrictQpuNotAllowed { name } if name == "@h"));
+    }
+
+    #[test]
+    fn lazy_qpu_call() {
+        let content = content("fn main() null { x: i32 = add(1 2) loop(x){ @h(@q0) } }");
+        assert!(check_execution_mode(&content, ExecutionMode::Lazy).is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+//! Function return checks against the declared return type.
+//!
+
+use crate::ir::hir::{Content, Expr, FnDef, Stmt, TypeName};
+use crate::ir::visit::{walk_stmt, ExprVisitor};
+use crate::semantics::infer::{infer_type, literal_fits};
+use crate::utils::errors::SemanticError;
+
+
+/// Return type of functions that return nothing.
+pub const UNIT_TYPE: &str = "null";
+
+
+/// Check every `return` in `fn_def`'s body, including the ones nested in
+/// meta-call bodies, against its return type. Only returned values whose
+/// type can be inferred (see [`infer_type`]) are compared, and literals
+/// fitting the return type (see [`literal_fits`]) are accepted.
+///
+/// A function without any `return` must have the [`UNIT_TYPE`].
+///
+pub fn check_return_type(fn_def: &FnDef, content: &Content) -> Vec<SemanticError> {
+    let mut returns = Returns {
+        ty: &fn_def.ty,
+        expected: fn_def.ty.name.to_string(),
+        content,
+        count: 0,
+        errors: vec![],
+    };
+    returns.visit_block(&fn_def.body);
+    if returns.count == 0 && returns.expected != UNIT_TYPE {
+        returns.errors.push(SemanticError::MissingReturn {
+            name: fn_def.name.to_string(),
+            expected: returns.expected,
+        });
+    }
+    returns.errors
+}
+
+/// Compares each returned value's type with the expected one.
+struct Returns<'a> {
+    ty: &'a TypeName,
+    expected: String,
+    content: &'a Content,
+    count: usize,
+    errors: Vec<SemanticError>,
+}
+
+impl ExprVisitor for Returns<'_> {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if let Stmt::Return(expr) = stmt {
+            self.count += 1;
+            if let Some(ty) = infer_type(expr, self.content)
+                && ty.name.to_string() != self.expected
+                && !literal_fits(expr, self.ty)
+            {
+                self.errors.push(SemanticError::ReturnTypeMismatch {
+                    expected: self.expected.clone(),
+                    found: ty.name.to_string(),
+                    span: match expr {
+                        Expr::Literal(lit) => lit.span(),
+                        _ => None,
+                    },
+                });
+            }
+        }
+        walk_stmt(self, stmt)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::hir::Content;
+    use crate::parse::parser::fn_program;
+    use crate::semantics::returns::check_return_type;
+    use crate::utils::errors::SemanticError;
+
+    fn check(code: &str) -> Vec<SemanticError> {
+        check_return_type(&fn_program::fn_def(code).unwrap(), &Content::Groups(vec![]))
+    }
+
+    #[test]
+    fn matching_return() {
+        assert!(check("fn answer() i64 { return 42 }").is_empty());
+        assert!(check("fn log(x: i64) null { print(x) }").is_empty());
+        assert!(check("fn g() i32 { return 1 }").is_empty());
+        assert!(check("fn half() f32 { return 0.5 }").is_empty());
+        assert!(!check("fn g() i32 { return 1.0 }").is_empty());
+    }
+
+    #[test]
+    fn mismatched_return() {
+        let errors = check(r#"fn answer() i64 { return "42" }"#);
+        let [SemanticError::ReturnTypeMismatch { expected, found, span }] = &errors[..] else {
+            panic!("expected a single return type mismatch");
+        };
+        assert_eq!((expected.as_str(), found.as_str()), ("i64", "str"));
+        assert_eq!(*span, Some((25, 29)));
+    }
+
+    #[test]
+    fn missing_return() {
+        let errors = check("fn answer() i64 { print(42) }");
+        assert!(matches!(&errors[..], [SemanticError::MissingReturn { name, .. }] if name == "answer"));
+    }
+}
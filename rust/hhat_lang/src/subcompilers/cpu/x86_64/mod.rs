@@ -1 +1,2 @@
-mod ir;
\ No newline at end of file
+#[cfg(feature = "jit")]
+mod ir;
@@ -0,0 +1,186 @@
+//! Project building: lower every function module with the chosen backend.
+//!
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use crate::backends::backend::BackendRegistry;
+use crate::ir::hir::Content;
+use crate::ir::ids::BackendKind;
+use crate::ir::modules::HIRModule;
+use crate::ir::project::SourceProject;
+use crate::utils::errors::BackendError;
+
+
+/// Artifact written for each module by [`build_project`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// HIR of every module as JSON
+    Json,
+    /// OpenQASM 3 code, from the QPU backend
+    Qasm,
+    /// Textual IR, from the CPU backend
+    Ir,
+}
+
+impl Emit {
+    pub fn all() -> [Emit; 3] {
+        [Emit::Json, Emit::Qasm, Emit::Ir]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Emit::Json => "json",
+            Emit::Qasm => "qasm",
+            Emit::Ir => "ir",
+        }
+    }
+
+    /// Backend the artifact is generated by, `None` if any backend will do.
+    pub fn backend(&self) -> Option<BackendKind> {
+        match self {
+            Emit::Json => None,
+            Emit::Qasm => Some(BackendKind::QPU),
+            Emit::Ir => Some(BackendKind::CPU),
+        }
+    }
+}
+
+/// Artifact from its name (`"json"`, `"qasm"` or `"ir"`), case-insensitive.
+impl FromStr for Emit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Emit::all()
+            .into_iter()
+            .find(|emit| emit.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names = Emit::all().map(|emit| emit.name());
+                format!("unknown artifact `{}`, expected one of: {}", s, names.join(", "))
+            })
+    }
+}
+
+
+/// Build `project` for the backend `kind`, writing one file per module to
+/// `out_dir` under its path relative to the project root:
+///
+/// - with no `emit`, or one generated by `kind`'s backend, the function
+///   modules are lowered with the backend registered for `kind`, with the
+///   backend's extension; constants and types modules produce no code.
+/// - with [`Emit::Json`], every module's HIR is written as JSON, which
+///   requires the `serde` feature.
+///
+/// The project is expected to have been checked already (see
+/// [`check_sources`](crate::toolchain::check::check_sources)).
+///
+/// Returns the paths of the written files.
+///
+pub fn build_project(
+    project: &SourceProject,
+    registry: &BackendRegistry,
+    kind: BackendKind,
+    emit: Option<Emit>,
+    out_dir: &Path,
+) -> Result<Vec<PathBuf>, BackendError> {
+    if let Some(emit) = emit
+        && emit.backend().is_some_and(|backend| backend != kind)
+    {
+        return Err(BackendError::InvalidEmit { backend: kind, emit: emit.name().to_string() });
+    }
+    let backend = match emit {
+        Some(Emit::Json) => None,
+        _ => Some(registry.get(kind).ok_or(BackendError::UnsupportedBackend(kind))?),
+    };
+    let mut written = vec![];
+    for source in &project.sources {
+        let module = HIRModule::from_source(source, &project.root)
+            .map_err(|e| BackendError::LoweringFailed(e.to_string()))?;
+        let (code, extension) = match backend {
+            None => (emit_json(&module)?, Emit::Json.name()),
+            Some(_) if !matches!(module.content, Content::Groups(_)) => continue,
+            Some(backend) => (backend.lower(&module)?, backend.extension()),
+        };
+        let rel = source.path.strip_prefix(&project.root).unwrap_or(&source.path);
+        let out = out_dir.join(rel).with_extension(extension);
+        out.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&out, code))
+            .map_err(|_| BackendError::CannotWriteOutput(out.clone()))?;
+        written.push(out);
+    }
+    Ok(written)
+}
+
+#[cfg(feature = "serde")]
+fn emit_json(module: &HIRModule) -> Result<String, BackendError> {
+    crate::ir::hir::to_json(&module.content).map_err(|e| BackendError::LoweringFailed(e.to_string()))
+}
+
+#[cfg(not(feature = "serde"))]
+fn emit_json(_module: &HIRModule) -> Result<String, BackendError> {
+    Err(BackendError::LoweringFailed(String::from("JSON output requires the `serde` feature")))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::backends::backend::BackendRegistry;
+    use crate::ir::ids::BackendKind;
+    use crate::ir::project::SourceProject;
+    use crate::toolchain::build::{build_project, Emit};
+    use crate::utils::errors::BackendError;
+
+    #[test]
+    fn build_qpu_program() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        fs::create_dir_all(root.join("circuits")).unwrap();
+        fs::write(root.join("circuits/bell.hat"), "fn bell() null { @h(@q0) @cx(@q0 @q1) }").unwrap();
+        fs::write(root.join("consts.hat"), "const n: i64").unwrap();
+
+        let project = SourceProject::new(root.to_str().unwrap()).unwrap();
+        let out_dir = dir.path().join("out");
+        let registry = BackendRegistry::with_defaults();
+        let written = build_project(&project, &registry, BackendKind::QPU, None, &out_dir).unwrap();
+
+        let qasm = out_dir.join("circuits/bell.qasm");
+        assert_eq!(written, vec![qasm.clone()]);
+        assert!(fs::read_to_string(qasm).unwrap().contains("cx q[0], q[1];"));
+    }
+
+    #[test]
+    fn build_unsupported_backend() {
+        let project = SourceProject::from_sources(Default::default(), vec![]);
+        let registry = BackendRegistry::with_defaults();
+        let res = build_project(&project, &registry, BackendKind::GPU, None, "out".as_ref());
+        assert!(matches!(res, Err(BackendError::UnsupportedBackend(BackendKind::GPU))));
+    }
+
+    #[test]
+    fn build_invalid_emit() {
+        let project = SourceProject::from_sources(Default::default(), vec![]);
+        let registry = BackendRegistry::with_defaults();
+        let res = build_project(&project, &registry, BackendKind::CPU, Some(Emit::Qasm), "out".as_ref());
+        assert!(matches!(res, Err(BackendError::InvalidEmit { backend: BackendKind::CPU, emit }) if emit == "qasm"));
+        assert_eq!("IR".parse(), Ok(Emit::Ir));
+        assert!("wasm".parse::<Emit>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn build_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = SourceProject::new("tests/fixtures/hir-module").unwrap();
+        let registry = BackendRegistry::new();
+        let written = build_project(&project, &registry, BackendKind::CPU, Some(Emit::Json), dir.path())
+            .unwrap();
+
+        let json = dir.path().join("geometry/point.json");
+        assert_eq!(written, vec![json.clone()]);
+        let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(json).unwrap()).unwrap();
+        assert!(value["Types"].is_array());
+    }
+}
@@ -0,0 +1,237 @@
+//! Project checking: parse and analyse every module without generating code.
+//!
+
+use std::path::{Path, PathBuf};
+use crate::backends::qasm::GATES;
+use crate::config::project::ProjectConfig;
+use crate::ir::hir::{Content, Expr, FnDef, GroupsDef};
+use crate::ir::ids::{BackendKind, Interner, SymbolContext};
+use crate::ir::modules::HIRModule;
+use crate::ir::project::{ModuleGraph, SourceProject};
+use crate::ir::visit::{walk_expr, ExprVisitor};
+use crate::passes::names::resolve_names;
+use crate::passes::unused::check_unused_imports;
+use crate::passes::validate::check_duplicates;
+use crate::runtime::interpreter::BUILTINS;
+use crate::semantics::calls::check_call;
+use crate::semantics::cast::check_casts;
+use crate::semantics::mode::{check_execution_mode, ExecutionMode};
+use crate::semantics::returns::check_return_type;
+use crate::utils::errors::{Diagnostic, ModuleError, ProjectError, SemanticError};
+
+
+/// Errors and warnings found in a single module.
+///
+#[derive(Debug)]
+pub struct ModuleReport {
+    pub path: PathBuf,
+    pub errors: Vec<ModuleError>,
+    pub warnings: Vec<Diagnostic>,
+}
+
+
+/// Parse every module of the project at `root` and run the semantic passes
+/// on the ones that parse, returning a report for each module with errors
+/// or warnings. Code is checked in the execution mode of the project's
+/// default backend.
+///
+pub fn check_project(root: &str) -> Result<Vec<ModuleReport>, ProjectError> {
+    let config = ProjectConfig::load(Path::new(root))?;
+    let mode = ExecutionMode::for_backend(config.default_backend);
+    Ok(check_sources(&SourceProject::new(root)?, mode))
+}
+
+/// Same as [`check_project`], on an already loaded project and in the given
+/// execution `mode`. Modules that were skipped while loading it are reported
+/// too.
+pub fn check_sources(project: &SourceProject, mode: ExecutionMode) -> Vec<ModuleReport> {
+    let skipped = project.errors.iter()
+        .filter_map(|err| match err {
+            ModuleError::InvalidEncoding { path } => {
+                Some((path, ModuleError::InvalidEncoding { path: path.clone() }))
+            }
+            ModuleError::InvalidPath { path } => {
+                Some((path, ModuleError::InvalidPath { path: path.clone() }))
+            }
+            _ => None,
+        })
+        .map(|(path, err)| ModuleReport { path: path.clone(), errors: vec![err], warnings: vec![] });
+    let parsed: Vec<_> = project.sources.iter()
+        .map(|source| (source, HIRModule::from_source(source, &project.root)))
+        .collect();
+    let graph = ModuleGraph::from_modules(project, parsed.iter().filter_map(|(_, m)| m.as_ref().ok()));
+    // an import cycle is reported on its first module
+    let mut cycle = graph.topo_order().err();
+    let checked = parsed.iter()
+        .filter_map(|(source, module)| {
+            let (mut errors, warnings) = match module {
+                Ok(module) => check_module(module, mode),
+                Err(err) => (vec![err.clone()], vec![]),
+            };
+            let id = project.module_id(&source.path);
+            if matches!(&cycle, Some(ModuleError::ImportCycle { modules }) if modules.first() == id.as_ref()) {
+                errors.extend(cycle.take());
+            }
+            (!errors.is_empty() || !warnings.is_empty())
+                .then(|| ModuleReport { path: source.path.clone(), errors, warnings })
+        })
+        .collect::<Vec<_>>();
+    skipped.chain(checked).collect()
+}
+
+fn check_module(module: &HIRModule, mode: ExecutionMode) -> (Vec<ModuleError>, Vec<Diagnostic>) {
+    let mut ctx = builtins_context();
+    let mut errors = check_duplicates(&module.content);
+    errors.extend(resolve_names(&module.imports, &module.content, &mut ctx));
+    let mut warnings = vec![];
+    if let Content::Groups(groups) = &module.content {
+        warnings = check_unused_imports(&module.imports, &module.content);
+        let semantic = check_execution_mode(&module.content, mode)
+            .into_iter()
+            .chain(fn_defs(groups).flat_map(|def| check_return_type(def, &module.content)))
+            .chain(check_calls(groups, &module.content))
+            .chain(check_casts(&module.content));
+        errors.extend(semantic.map(ModuleError::from));
+    }
+    (errors, warnings)
+}
+
+fn fn_defs(groups: &[GroupsDef]) -> impl Iterator<Item = &FnDef> {
+    groups.iter().filter_map(|group| match group {
+        GroupsDef::FnDef(def) => Some(def),
+        _ => None,
+    })
+}
+
+/// Check every call to a function defined in the module against its
+/// definition.
+fn check_calls(groups: &[GroupsDef], content: &Content) -> Vec<SemanticError> {
+    let mut checker = CallChecker { groups, content, errors: vec![] };
+    for def in fn_defs(groups) {
+        checker.visit_block(&def.body);
+    }
+    checker.errors
+}
+
+
+struct CallChecker<'a> {
+    groups: &'a [GroupsDef],
+    content: &'a Content,
+    errors: Vec<SemanticError>,
+}
+
+impl ExprVisitor for CallChecker<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let Expr::Call { callee, .. } = expr
+            && let Some(def) = fn_defs(self.groups).find(|def| &def.name == callee)
+        {
+            self.errors.extend(check_call(expr, def, self.content));
+        }
+        walk_expr(self, expr)
+    }
+}
+
+/// Scope holding the interpreter built-ins and the QPU gates (`@h`, ...).
+fn builtins_context() -> SymbolContext {
+    let mut interner = Interner::new();
+    let mut ctx = SymbolContext::new();
+    let gates = GATES.iter().map(|gate| format!("{}{}", BackendKind::QPU.sugar_str(), gate));
+    for name in BUILTINS.iter().map(|b| b.to_string()).chain(gates) {
+        let id = interner.intern(&name);
+        ctx.insert(&name, id);
+    }
+    ctx
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::toolchain::check::check_project;
+    use crate::utils::errors::{ModuleError, SemanticError};
+
+    #[test]
+    fn check_reports_broken_module() {
+        let reports = check_project("tests/fixtures/check-project").unwrap();
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].path.ends_with("broken.hat"));
+        assert!(matches!(reports[0].errors[..], [ModuleError::ParseFailed { .. }]));
+    }
+
+    #[test]
+    fn check_qpu_builtins() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("hat.toml"), "default_backend = \"QPU\"").unwrap();
+        std::fs::write(dir.path().join("bell.hat"), "fn bell() null { @h(@q0) @cx(@q0 @q1) }").unwrap();
+        assert!(check_project(dir.path().to_str().unwrap()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn check_strict_qpu_call() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("bell.hat"), "fn bell() null { @h(@q0) }").unwrap();
+        let reports = check_project(dir.path().to_str().unwrap()).unwrap();
+        assert!(matches!(
+            reports[0].errors[..],
+            [ModuleError::Semantic(SemanticError::StrictQpuNotAllowed { .. })]
+        ));
+    }
+
+    #[test]
+    fn check_reports_return_and_call_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("main.hat"),
+            "fn one() i64 { return 1.0 } fn id(x:i64) i64 { return x } fn main() null { id(1 2) }",
+        ).unwrap();
+        let reports = check_project(dir.path().to_str().unwrap()).unwrap();
+        assert!(matches!(
+            reports[0].errors[..],
+            [
+                ModuleError::Semantic(SemanticError::ReturnTypeMismatch { .. }),
+                ModuleError::Semantic(SemanticError::ArityMismatch { expected: 1, found: 2 }),
+            ]
+        ));
+    }
+
+    #[test]
+    fn check_accepts_narrower_literals() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("main.hat"),
+            "fn g() i32 { return 1 } fn h(a:i32) i32 { return a } fn main() null { h(2) }",
+        ).unwrap();
+        assert!(check_project(dir.path().to_str().unwrap()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn check_reports_import_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.hat"), "use fns b.g\nfn f() null { g() }").unwrap();
+        std::fs::write(dir.path().join("b.hat"), "use fns a.f\nfn g() null { f() }").unwrap();
+        let reports = check_project(dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].path.ends_with("a.hat"));
+        assert!(matches!(&reports[0].errors[..], [ModuleError::ImportCycle { modules }] if modules.len() == 2));
+    }
+
+    #[test]
+    fn check_reports_unused_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.hat"), "use fns math.sub\nfn main() null { }").unwrap();
+        let reports = check_project(dir.path().to_str().unwrap()).unwrap();
+        assert!(reports[0].errors.is_empty());
+        assert_eq!(reports[0].warnings.len(), 1);
+    }
+
+    #[test]
+    fn check_reports_semantic_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.hat"), "fn f() i64 { return g(1) } fn f() i64 { return 1 }").unwrap();
+        let reports = check_project(dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert!(matches!(
+            reports[0].errors[..],
+            [ModuleError::DuplicateDefinition { .. }, ModuleError::UndefinedSymbol { .. }]
+        ));
+    }
+}
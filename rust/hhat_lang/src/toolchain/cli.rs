@@ -0,0 +1,144 @@
+//! Command line interface of the `hhat` toolchain.
+//!
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use clap::{Parser, Subcommand};
+use crate::backends::backend::BackendRegistry;
+use crate::config::project::ProjectConfig;
+use crate::ir::ids::BackendKind;
+use crate::ir::project::SourceProject;
+use crate::semantics::mode::ExecutionMode;
+use crate::toolchain::build::{build_project, Emit};
+use crate::toolchain::check::{check_sources, ModuleReport};
+use crate::toolchain::repl::Repl;
+use crate::utils::errors::Diagnostic;
+
+
+#[derive(Debug, Parser)]
+#[command(name = "hhat", version, about = "H-hat language toolchain")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Parse and check a project without generating code
+    Check {
+        /// Project root directory
+        #[arg(default_value = ".")]
+        path: String,
+    },
+    /// Check a project and lower it to the chosen backend
+    Build {
+        /// Project root directory
+        #[arg(default_value = ".")]
+        path: String,
+        /// Target backend, the project's `default_backend` by default
+        #[arg(long)]
+        backend: Option<BackendKind>,
+        /// Output directory, `<path>/build` by default
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Artifact to write (json, qasm or ir), the backend's own code by default
+        #[arg(long)]
+        emit: Option<Emit>,
+    },
+    /// Evaluate statements and expressions interactively on the CPU
+    Repl,
+}
+
+pub fn run() -> ExitCode {
+    match Cli::parse().command {
+        Command::Check { path } => check(&path),
+        Command::Build { path, backend, output, emit } => build(&path, backend, emit, output),
+        Command::Repl => repl(),
+    }
+}
+
+/// Load the project at `path` and check it in the execution mode of
+/// `backend`, printing its errors and warnings, if any.
+fn load_checked(path: &str, backend: BackendKind) -> Option<SourceProject> {
+    let project = match SourceProject::new(path) {
+        Ok(project) => project,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            return None;
+        }
+    };
+    let reports = check_sources(&project, ExecutionMode::for_backend(backend));
+    print_reports(&project, &reports);
+    reports.iter().all(|report| report.errors.is_empty()).then_some(project)
+}
+
+/// Print each error and warning of `reports` rendered against its module's
+/// source in `project`.
+fn print_reports(project: &SourceProject, reports: &[ModuleReport]) {
+    for report in reports {
+        let source = project.sources.iter()
+            .find(|source| source.path == report.path)
+            .map_or("", |source| source.raw_code.as_str());
+        let diagnostics = report.errors.iter().map(Diagnostic::from).chain(report.warnings.iter().cloned());
+        for diagnostic in diagnostics {
+            eprintln!("{}: {}", report.path.display(), diagnostic.render(source));
+        }
+    }
+}
+
+/// Default backend from the `hat.toml` of the project at `path`, printing
+/// the error if the config is invalid.
+fn default_backend(path: &str) -> Option<BackendKind> {
+    match ProjectConfig::load(Path::new(path)) {
+        Ok(config) => Some(config.default_backend),
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            None
+        }
+    }
+}
+
+fn check(path: &str) -> ExitCode {
+    let Some(backend) = default_backend(path) else {
+        return ExitCode::FAILURE;
+    };
+    match load_checked(path, backend) {
+        Some(_) => {
+            println!("{}: ok", path);
+            ExitCode::SUCCESS
+        }
+        None => ExitCode::FAILURE,
+    }
+}
+
+fn build(path: &str, backend: Option<BackendKind>, emit: Option<Emit>, output: Option<PathBuf>) -> ExitCode {
+    let Some(backend) = backend.or_else(|| default_backend(path)) else {
+        return ExitCode::FAILURE;
+    };
+    let Some(project) = load_checked(path, backend) else {
+        return ExitCode::FAILURE;
+    };
+    let out_dir = output.unwrap_or_else(|| project.root.join("build"));
+    match build_project(&project, &BackendRegistry::with_defaults(), backend, emit, &out_dir) {
+        Ok(written) => {
+            for file in written {
+                println!("wrote {}", file.display());
+            }
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn repl() -> ExitCode {
+    match Repl::new().run(std::io::stdin().lock(), std::io::stdout()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("repl: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
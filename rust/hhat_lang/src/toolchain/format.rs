@@ -0,0 +1,223 @@
+//! Source formatting: print a parsed module back in canonical syntax.
+//!
+
+use std::fmt::Write;
+use itertools::Itertools;
+use crate::ir::hir::{
+    prefix_modifiers, Assign, Block, ConstDef, Content, EnumMember, Expr, FnDef, GroupsDef,
+    MetaCall, OptionBody, Param, Stmt, StructDef, TypeDef,
+};
+
+
+const INDENT: &str = "  ";
+
+/// Pretty-print `content` with one definition per paragraph, one statement
+/// or member per line, and blocks indented by two spaces.
+///
+/// The output parses back to the same HIR.
+///
+pub fn format_module(content: &Content) -> String {
+    let items: Vec<String> = match content {
+        Content::Consts(consts) => consts.iter().map(format_const).collect(),
+        Content::Types(types) => types.iter().map(format_type).collect(),
+        Content::Groups(groups) => groups.iter().map(format_group).collect(),
+    };
+    let mut out = items.join("\n\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+fn format_const(def: &ConstDef) -> String {
+    format!("{}const {}: {}", prefix_modifiers(&def.modifiers), def.name, def.ty)
+}
+
+fn format_type(def: &TypeDef) -> String {
+    match def {
+        TypeDef::StructDef(def) => format_struct(def),
+        TypeDef::EnumDef { name, members, modifiers } => {
+            let members = members.iter().map(|m| match m {
+                EnumMember::KindMember(name) => name.to_string(),
+                EnumMember::StructMember(def) => format!(
+                    "{}{{ {} }}",
+                    def.name,
+                    def.members.iter().format_with(" ", |m, g| g(&format_args!("{}: {}", m.name, m.ty)))
+                ),
+            });
+            format!("{}enum {} {}", prefix_modifiers(modifiers), name, indented("{", members, "}"))
+        }
+        TypeDef::PrimitiveDef(def) => format!("{:?}", def).to_lowercase(),
+        TypeDef::NamedType { name } => name.to_string(),
+    }
+}
+
+fn format_struct(def: &StructDef) -> String {
+    let members = def.members.iter().map(|m| format!("{}: {}", m.name, m.ty));
+    format!("{}struct {} {}", prefix_modifiers(&def.modifiers), def.name, indented("{", members, "}"))
+}
+
+fn format_group(def: &GroupsDef) -> String {
+    match def {
+        GroupsDef::FnDef(def) => format_fn(def),
+        GroupsDef::ModifierDef(def) => {
+            let params = def.params.iter().flatten().map(format_param).join(" ");
+            format!(
+                "{}modifier {}({}) {}",
+                prefix_modifiers(&def.modifiers),
+                def.name,
+                params,
+                format_block(&def.body)
+            )
+        }
+        GroupsDef::MetaFnDef(def) => format!(
+            "{}metafn {}({}) {}",
+            prefix_modifiers(&def.modifiers),
+            def.name,
+            def.params.iter().map(format_param).join(" "),
+            format_block(&def.body)
+        ),
+    }
+}
+
+fn format_fn(def: &FnDef) -> String {
+    format!(
+        "{}fn {}({}) {} {}",
+        prefix_modifiers(&def.modifiers),
+        def.name,
+        def.params.iter().map(format_param).join(" "),
+        def.ty,
+        format_block(&def.body)
+    )
+}
+
+fn format_param(param: &Param) -> String {
+    format!("{}{}: {}", prefix_modifiers(&param.modifiers), param.name, param.ty)
+}
+
+fn format_block(block: &Block) -> String {
+    indented("{", block.iter().map(format_stmt), "}")
+}
+
+fn format_stmt(stmt: &Stmt) -> String {
+    match stmt {
+        Stmt::Assign(Assign::Single { name, value, modifiers }) => {
+            format!("{}{} = {}", prefix_modifiers(modifiers), name, format_expr(value))
+        }
+        Stmt::DeclareAssign { name, ty, modifiers, value } => {
+            format!("{}{}: {} = {}", prefix_modifiers(modifiers), name, ty, format_expr(value))
+        }
+        Stmt::Expr(expr) => format_expr(expr),
+        Stmt::Return(expr) => format!("return {}", format_expr(expr)),
+        _ => stmt.to_string(),
+    }
+}
+
+/// Meta-calls get their bodies indented one level deeper; other expressions
+/// stay on one line.
+fn format_expr(expr: &Expr) -> String {
+    let Expr::MetaCall(call) = expr else {
+        return expr.to_string();
+    };
+    let options = |options: &[OptionBody]| {
+        options.iter().map(|o| format!("{}:{}", o.opt, format_block(&o.body))).collect::<Vec<_>>()
+    };
+    match call {
+        MetaCall::Optn { name, options: opts, modifiers } => format!(
+            "{}{}",
+            indented(&format!("{}(", name), options(opts).into_iter(), ")"),
+            modifiers.iter().format("")
+        ),
+        MetaCall::Bdn { name, args, body, modifiers } => format!(
+            "{}({}){}{}",
+            name,
+            args.iter().format(" "),
+            modifiers.iter().format(""),
+            format_block(body)
+        ),
+        MetaCall::OptBdn { name, args, body, modifiers } => {
+            let open = format!("{}({}){}{{", name, args.iter().format(" "), modifiers.iter().format(""));
+            indented(&open, options(body).into_iter(), "}")
+        }
+    }
+}
+
+/// `lines` between `open` and `close`, each on its own line indented by
+/// [`INDENT`] (nested lines included), or `open` and `close` only if empty.
+fn indented(open: &str, lines: impl Iterator<Item = String>, close: &str) -> String {
+    let mut out = String::from(open);
+    let mut empty = true;
+    for line in lines {
+        for sub in line.lines() {
+            write!(out, "\n{}{}", INDENT, sub).unwrap();
+        }
+        empty = false;
+    }
+    if !empty {
+        out.push('\n');
+    }
+    out.push_str(close);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::parser::{const_program, fn_program, type_program};
+    use crate::toolchain::format::format_module;
+
+    #[test]
+    fn format_fn_module() {
+        let source = "fn  add(a:i32  b:i32) i32 {return a\n x:i32=add(a b) ; loop(i){ print(i) } }\nfn noop() null {}";
+        let (_, content) = fn_program::module(source).unwrap();
+        let formatted = format_module(&content);
+        assert_eq!(
+            formatted,
+            "fn add(a: i32 b: i32) i32 {\n  return a\n  x: i32 = add(a b)\n  loop(i){\n    print(i)\n  }\n}\n\nfn noop() null {}\n"
+        );
+        let (_, reparsed) = fn_program::module(&formatted).unwrap();
+        assert_eq!(reparsed, content);
+        assert_eq!(format_module(&reparsed), formatted);
+    }
+
+    #[test]
+    fn format_nested_bodies() {
+        let source = "fn main() null { loop(i){ switch(i){ 1:{ print(i) } 2:{} } } x: i64 = match(a:{ return 1 }) }";
+        let (_, content) = fn_program::module(source).unwrap();
+        let formatted = format_module(&content);
+        assert_eq!(
+            formatted,
+            "fn main() null {\n  loop(i){\n    switch(i){\n      1:{\n        print(i)\n      }\n      2:{}\n    }\n  }\n  x: i64 = match(\n    a:{\n      return 1\n    }\n  )\n}\n"
+        );
+        let (_, reparsed) = fn_program::module(&formatted).unwrap();
+        assert_eq!(reparsed, content);
+        assert_eq!(format_module(&reparsed), formatted);
+    }
+
+    #[test]
+    fn format_modified_types() {
+        let source = "fn f(a:<mut> i32) <&> i32 { <mut> x:<&>i64 = a y: u64 = a * <&> u64 }";
+        let (_, content) = fn_program::module(source).unwrap();
+        let formatted = format_module(&content);
+        assert_eq!(
+            formatted,
+            "fn f(a: <mut> i32) <&> i32 {\n  <mut> x: <&> i64 = a\n  y: u64 = a * <&> u64\n}\n"
+        );
+        assert_eq!(fn_program::module(&formatted).unwrap().1, content);
+    }
+
+    #[test]
+    fn format_types_and_consts() {
+        let (_, content) = type_program::module("struct point{x:i32 y:i32} enum color{ red rgb{r:u8 g:u8} }").unwrap();
+        let formatted = format_module(&content);
+        assert_eq!(
+            formatted,
+            "struct point {\n  x: i32\n  y: i32\n}\n\nenum color {\n  red\n  rgb{ r: u8 g: u8 }\n}\n"
+        );
+        assert_eq!(type_program::module(&formatted).unwrap().1, content);
+
+        let (_, content) = const_program::module("<&> const PI:f64 const E: f64").unwrap();
+        let formatted = format_module(&content);
+        assert_eq!(formatted, "<&> const PI: f64\n\nconst E: f64\n");
+        assert_eq!(const_program::module(&formatted).unwrap().1, content);
+    }
+}
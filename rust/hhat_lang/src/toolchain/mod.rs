@@ -0,0 +1,5 @@
+pub(crate) mod build;
+pub(crate) mod check;
+pub(crate) mod cli;
+pub(crate) mod format;
+pub(crate) mod repl;
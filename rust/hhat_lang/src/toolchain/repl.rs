@@ -0,0 +1,89 @@
+//! Interactive prompt evaluating statements and expressions on the CPU.
+//!
+
+use std::io::{self, BufRead, Write};
+use crate::ir::hir::Stmt;
+use crate::parse::parser::fn_program;
+use crate::runtime::interpreter::Interpreter;
+use crate::runtime::value::Value;
+use crate::utils::errors::{Diagnostic, ModuleError};
+
+
+pub const PROMPT: &str = "hhat> ";
+
+
+/// REPL session: variables defined on a line stay defined on the next ones.
+///
+#[derive(Default)]
+pub struct Repl {
+    interpreter: Interpreter,
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse `line` as a statement and evaluate it, returning the value of
+    /// an expression or `return`, and nothing for declarations, assignments
+    /// and blank lines.
+    pub fn eval_line(&mut self, line: &str) -> Result<Option<Value>, Diagnostic> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        let stmt = fn_program::stmt(line)
+            .map_err(|err| Diagnostic::from(&ModuleError::from(err)))?;
+        let res = match &stmt {
+            Stmt::Expr(expr) => self.interpreter.eval_expr(expr).map(Some),
+            stmt => self.interpreter.eval_stmt(stmt),
+        };
+        res.map_err(|err| Diagnostic::error(err.to_string(), None))
+    }
+
+    /// Read lines from `input` until it ends, printing each resulting value
+    /// to `output` and each error to stderr.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        write!(output, "{}", PROMPT)?;
+        output.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            match self.eval_line(&line) {
+                Ok(Some(value)) => writeln!(output, "{}", value)?,
+                Ok(None) => {}
+                Err(diagnostic) => eprintln!("{}", diagnostic.render(line.trim())),
+            }
+            write!(output, "{}", PROMPT)?;
+            output.flush()?;
+        }
+        writeln!(output)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::value::Value;
+    use crate::toolchain::repl::Repl;
+
+    #[test]
+    fn scripted_session() {
+        let mut repl = Repl::new();
+        let mut last = None;
+        for line in ["x: i64 = 1", "", "y: i64 = add(x 2)", "undefined(1)", "x = mul(y 10)", "x"] {
+            if let Ok(Some(value)) = repl.eval_line(line) {
+                last = Some(value);
+            }
+        }
+        assert_eq!(last, Some(Value::Int(30)));
+        assert!(repl.eval_line("z: i64 = ").is_err());
+        assert!(repl.eval_line("div(x 0)").is_err());
+    }
+
+    #[test]
+    fn run_prints_values() {
+        let mut out = vec![];
+        Repl::new().run("a: f64 = 1.5\nmul(a 2)\n\"hoi\"\n".as_bytes(), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "hhat> hhat> 3.0\nhhat> \"hoi\"\nhhat> \n");
+    }
+}
@@ -1,14 +1,91 @@
 use std::fmt::{Display, Formatter};
 use std::error::Error;
+use std::path::PathBuf;
+use peg::error::ParseError;
+use peg::str::LineCol;
+use crate::ir::ids::{BackendKind, ModuleId};
 
+#[derive(Debug)]
 pub enum ProjectError {
     ProjectNotFound,
+    ConfigInvalid(String),
+    NoModulesFound,
+    RootNotADirectory(PathBuf),
+    Module(ModuleError),
 }
 
-#[derive(Debug)]
+impl Display for ProjectError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectError::ProjectNotFound => write!(f, "Project not found"),
+            ProjectError::ConfigInvalid(reason) => write!(f, "Invalid project config: {}", reason),
+            ProjectError::NoModulesFound => write!(f, "No modules found in the project"),
+            ProjectError::RootNotADirectory(path) => {
+                write!(f, "Project root {} is not a directory", path.display())
+            }
+            ProjectError::Module(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ProjectError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProjectError::Module(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ModuleError> for ProjectError {
+    fn from(err: ModuleError) -> Self {
+        ProjectError::Module(err)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum ModuleError {
     ModuleNotFound,
     CannotReadFile,
+    EmptyPath,
+    ParseFailed { offset: usize, expected: Vec<String> },
+    UndefinedSymbol { name: String, span: Option<(usize, usize)> },
+    MixedContent,
+    EmptyContent,
+    DuplicateDefinition { name: String },
+    InvalidPattern(String),
+    ImportCycle { modules: Vec<ModuleId> },
+    InvalidEncoding { path: PathBuf },
+    InvalidPath { path: PathBuf },
+    Semantic(SemanticError),
+}
+
+#[derive(Debug)]
+pub enum BackendError {
+    UnsupportedConstruct { construct: String },
+    UnsupportedBackend(BackendKind),
+    LoweringFailed(String),
+    CannotWriteOutput(PathBuf),
+    InvalidEmit { backend: BackendKind, emit: String },
+}
+
+#[derive(Debug, Clone)]
+pub enum SemanticError {
+    StrictQpuNotAllowed { name: String },
+    CannotInfer { name: String },
+    ArgTypeMismatch {
+        param: String,
+        expected: String,
+        found: String,
+        span: Option<(usize, usize)>,
+    },
+    ArityMismatch { expected: usize, found: usize },
+    ConflictingShots { first: u32, second: u32 },
+    NonExhaustiveMatch { missing: Vec<String> },
+    InvalidCast { from: String, to: String, span: Option<(usize, usize)> },
+    NoSuchMember { ty: String, member: String },
+    ReturnTypeMismatch { expected: String, found: String, span: Option<(usize, usize)> },
+    MissingReturn { name: String, expected: String },
 }
 
 impl Display for ModuleError {
@@ -16,8 +93,278 @@ impl Display for ModuleError {
         match self {
             ModuleError::ModuleNotFound => write!(f, "Module not found"),
             ModuleError::CannotReadFile => write!(f, "Cannot read file"),
+            ModuleError::EmptyPath => write!(f, "Module path is empty"),
+            ModuleError::ParseFailed { offset, expected } => write!(
+                f,
+                "parse error at offset {}, expected: {}",
+                offset,
+                expected.join(", ")
+            ),
+            ModuleError::UndefinedSymbol { name, span: Some((start, _)) } => {
+                write!(f, "Undefined symbol `{}` at offset {}", name, start)
+            }
+            ModuleError::UndefinedSymbol { name, span: None } => {
+                write!(f, "Undefined symbol `{}`", name)
+            }
+            ModuleError::MixedContent => {
+                write!(f, "Constants, types and groups cannot be mixed in the same file")
+            }
+            ModuleError::EmptyContent => write!(f, "Module has no definitions"),
+            ModuleError::DuplicateDefinition { name } => {
+                write!(f, "`{}` is defined more than once", name)
+            }
+            ModuleError::InvalidPattern(pattern) => write!(f, "Invalid glob pattern `{}`", pattern),
+            ModuleError::ImportCycle { modules } => write!(
+                f,
+                "Import cycle between modules {}",
+                modules.iter().map(|m| m.0.to_string()).collect::<Vec<_>>().join(" -> ")
+            ),
+            ModuleError::InvalidEncoding { path } => {
+                write!(f, "{} is not valid UTF-8", path.display())
+            }
+            ModuleError::InvalidPath { path } => {
+                write!(f, "Path {} is not valid UTF-8", path.display())
+            }
+            ModuleError::Semantic(err) => write!(f, "{}", err),
         }
     }
 }
 
-impl Error for ModuleError {}
\ No newline at end of file
+impl Error for ModuleError {}
+
+impl From<SemanticError> for ModuleError {
+    fn from(err: SemanticError) -> Self {
+        ModuleError::Semantic(err)
+    }
+}
+
+impl Display for BackendError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackendError::UnsupportedConstruct { construct } => {
+                write!(f, "Unsupported construct `{}`", construct)
+            }
+            BackendError::UnsupportedBackend(kind) => write!(f, "Unsupported backend {:?}", kind),
+            BackendError::LoweringFailed(reason) => write!(f, "Lowering failed: {}", reason),
+            BackendError::CannotWriteOutput(path) => {
+                write!(f, "Cannot write output file {}", path.display())
+            }
+            BackendError::InvalidEmit { backend, emit } => {
+                write!(f, "Cannot emit {} for backend {:?}", emit, backend)
+            }
+        }
+    }
+}
+
+impl Error for BackendError {}
+
+impl Display for SemanticError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SemanticError::StrictQpuNotAllowed { name } => {
+                write!(f, "QPU call `{}` cannot run in strict mode", name)
+            }
+            SemanticError::CannotInfer { name } => {
+                write!(f, "Cannot infer the type of `{}`", name)
+            }
+            SemanticError::ArgTypeMismatch { param, expected, found, .. } => write!(
+                f,
+                "Argument for `{}` expected type `{}`, found `{}`",
+                param, expected, found
+            ),
+            SemanticError::ArityMismatch { expected, found } => {
+                write!(f, "Expected {} argument(s), found {}", expected, found)
+            }
+            SemanticError::ConflictingShots { first, second } => {
+                write!(f, "Conflicting shots: {} and {}", first, second)
+            }
+            SemanticError::NonExhaustiveMatch { missing } => {
+                write!(f, "Non-exhaustive options, missing: {}", missing.join(", "))
+            }
+            SemanticError::InvalidCast { from, to, .. } => {
+                write!(f, "Cannot cast `{}` to `{}`", from, to)
+            }
+            SemanticError::NoSuchMember { ty, member } => {
+                write!(f, "Type `{}` has no member `{}`", ty, member)
+            }
+            SemanticError::ReturnTypeMismatch { expected, found, .. } => {
+                write!(f, "Expected return type `{}`, found `{}`", expected, found)
+            }
+            SemanticError::MissingReturn { name, expected } => {
+                write!(f, "Function `{}` must return a value of type `{}`", name, expected)
+            }
+        }
+    }
+}
+
+impl Error for SemanticError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// Error or warning to show to the user, pointing at the source code when
+/// its byte span (start, end) is known.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Option<(usize, usize)>,
+    pub severity: Severity,
+}
+
+impl Diagnostic {
+    pub fn error(message: String, span: Option<(usize, usize)>) -> Self {
+        Self { message, span, severity: Severity::Error }
+    }
+
+    pub fn warning(message: String, span: Option<(usize, usize)>) -> Self {
+        Self { message, span, severity: Severity::Warning }
+    }
+
+    /// Render the diagnostic with the offending line of `source` and a caret
+    /// underline below the span, ex:
+    ///
+    /// ```text
+    /// error: Undefined symbol `y`
+    ///  --> 1:8
+    ///   |
+    /// 1 | return y
+    ///   |        ^
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let header = format!("{}: {}", self.severity, self.message);
+        let Some((start, end)) = self.span else {
+            return header;
+        };
+        let start = start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |n| n + 1);
+        let line_end = source[start..].find('\n').map_or(source.len(), |n| start + n);
+        let line = &source[line_start..line_end];
+        let line_no = source[..line_start].matches('\n').count() + 1;
+        let col = source[line_start..start].chars().count();
+        let width = source[start..end.clamp(start, line_end)].chars().count().max(1);
+        let gutter = " ".repeat(line_no.to_string().len());
+        format!(
+            "{header}\n{gutter}--> {line_no}:{}\n{gutter} |\n{line_no} | {line}\n{gutter} | {}{}",
+            col + 1,
+            " ".repeat(col),
+            "^".repeat(width)
+        )
+    }
+}
+
+impl From<&ModuleError> for Diagnostic {
+    fn from(err: &ModuleError) -> Self {
+        let span = match err {
+            ModuleError::ParseFailed { offset, .. } => Some((*offset, offset + 1)),
+            ModuleError::UndefinedSymbol { span, .. } => *span,
+            ModuleError::Semantic(err) => return Diagnostic::from(err),
+            _ => None,
+        };
+        Diagnostic::error(err.to_string(), span)
+    }
+}
+
+/// Only type mismatches and casts on literals carry a span so far.
+impl From<&SemanticError> for Diagnostic {
+    fn from(err: &SemanticError) -> Self {
+        let span = match err {
+            SemanticError::ArgTypeMismatch { span, .. }
+            | SemanticError::ReturnTypeMismatch { span, .. }
+            | SemanticError::InvalidCast { span, .. } => *span,
+            _ => None,
+        };
+        Diagnostic::error(err.to_string(), span)
+    }
+}
+
+impl From<ParseError<LineCol>> for ModuleError {
+    fn from(err: ParseError<LineCol>) -> Self {
+        let mut expected: Vec<String> = err.expected
+            .tokens()
+            .map(String::from)
+            .collect();
+        expected.sort();
+        ModuleError::ParseFailed { offset: err.location.offset, expected }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::parse::parser::fn_program;
+    use crate::ir::ids::{BackendKind, ModuleId};
+    use crate::utils::errors::{BackendError, Diagnostic, ModuleError, ProjectError, SemanticError};
+
+    #[test]
+    fn parse_error_into_module_error() {
+        let Err(err) = fn_program::start("[a !]").map_err(ModuleError::from) else {
+            panic!("expected \"[a !]\" to fail parsing");
+        };
+        let ModuleError::ParseFailed { offset, expected } = &err else {
+            panic!("expected a parse failure");
+        };
+        assert_eq!(*offset, 4);
+        assert!(!expected.is_empty());
+        assert!(err.to_string().starts_with("parse error at offset 4, expected: "));
+    }
+
+    #[test]
+    fn backend_error_display() {
+        let err = BackendError::UnsupportedConstruct { construct: String::from("@foo") };
+        assert_eq!(err.to_string(), "Unsupported construct `@foo`");
+        let err = BackendError::UnsupportedBackend(BackendKind::GPU);
+        assert_eq!(err.to_string(), "Unsupported backend GPU");
+        let err = BackendError::LoweringFailed(String::from("bad qubit"));
+        assert_eq!(err.to_string(), "Lowering failed: bad qubit");
+    }
+
+    #[test]
+    fn project_error_display() {
+        assert_eq!(ProjectError::ProjectNotFound.to_string(), "Project not found");
+        let err = ProjectError::ConfigInvalid(String::from("bad shots"));
+        assert_eq!(err.to_string(), "Invalid project config: bad shots");
+        assert_eq!(ProjectError::NoModulesFound.to_string(), "No modules found in the project");
+        let err = ProjectError::RootNotADirectory(PathBuf::from("nowhere"));
+        assert_eq!(err.to_string(), "Project root nowhere is not a directory");
+        let err = ProjectError::from(ModuleError::CannotReadFile);
+        assert_eq!(err.to_string(), "Cannot read file");
+    }
+
+    #[test]
+    fn diagnostic_render() {
+        let source = "fn main() null {\n  return y\n}";
+        let start = source.find('y').unwrap();
+        let err = ModuleError::UndefinedSymbol { name: String::from("y"), span: Some((start, start + 1)) };
+        let rendered = Diagnostic::from(&err).render(source);
+        assert_eq!(
+            rendered,
+            "error: Undefined symbol `y` at offset 26\n --> 2:10\n  |\n2 |   return y\n  |          ^"
+        );
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[3].find('y'), lines[4].find('^'));
+
+        let err = fn_program::fn_def("fn broken(x:i64 i64 {}").unwrap_err();
+        let diagnostic = Diagnostic::from(&ModuleError::from(err));
+        assert_eq!(diagnostic.span, Some((20, 21)));
+        assert!(diagnostic.render("fn broken(x:i64 i64 {}").ends_with(&format!("\n  | {}^", " ".repeat(20))));
+
+        let err = SemanticError::CannotInfer { name: String::from("x") };
+        assert_eq!(Diagnostic::from(&err).render(source), "error: Cannot infer the type of `x`");
+        let warning = Diagnostic::warning(String::from("unused"), Some((0, 2)));
+        assert!(warning.render(source).ends_with("1 | fn main() null {\n  | ^^"));
+    }
+}
@@ -0,0 +1,14 @@
+use std::process::Command;
+
+#[test]
+fn build_uses_the_default_backend() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("hat.toml"), "default_backend = \"QPU\"").unwrap();
+    std::fs::write(dir.path().join("bell.hat"), "fn bell() null { @h(@q0) @cx(@q0 @q1) }").unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_hhat_lang"))
+        .args(["build", dir.path().to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(dir.path().join("build/bell.qasm").is_file());
+}
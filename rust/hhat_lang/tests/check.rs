@@ -0,0 +1,14 @@
+use std::process::Command;
+
+#[test]
+fn check_reports_broken_module() {
+    let out = Command::new(env!("CARGO_BIN_EXE_hhat_lang"))
+        .args(["check", "tests/fixtures/check-project"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(!out.status.success());
+    assert!(stderr.contains("broken.hat: error: parse error"));
+    assert!(stderr.contains("1 | fn broken(x:i64 i64 { return x }\n"));
+    assert!(!stderr.contains("good.hat"));
+}